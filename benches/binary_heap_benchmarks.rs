@@ -1,5 +1,5 @@
 use binary_heap::{BinaryHeap, HeapKind};
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 use rand::distributions::Standard;
 // use rand::seq::{IteratorRandom, SliceRandom};
 use rand::{thread_rng, Rng};
@@ -37,5 +37,195 @@ pub fn benchmark_extract_object(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_insert, benchmark_extract_object);
+pub fn benchmark_extract_small(c: &mut Criterion) {
+    for len in [1, 2, 3] {
+        let data: Vec<i32> = (0..len).collect();
+        c.bench_function(&format!("benchmark extract object len {}", len), |b| {
+            b.iter_batched(
+                || BinaryHeap::<i32, fnv::FnvBuildHasher>::heapify(&data, HeapKind::Min),
+                |mut heap| heap.extract_object(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+pub fn benchmark_into_sorted_vec(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let num_items_to_insert: usize = black_box(10000);
+    // Duplicate heavy data, where the index table maintenance is the most expensive.
+    let data: Vec<i32> = (0..num_items_to_insert)
+        .map(|_| rng.gen_range(0..50))
+        .collect();
+    let build_heap = || BinaryHeap::<i32, fnv::FnvBuildHasher>::heapify(&data, HeapKind::Min);
+    c.bench_function("benchmark into_sorted_vec", |b| {
+        b.iter_batched(
+            build_heap,
+            |heap| heap.into_sorted_vec(),
+            BatchSize::LargeInput,
+        )
+    });
+    c.bench_function("benchmark into_sorted_vec_fast", |b| {
+        b.iter_batched(
+            build_heap,
+            |heap| heap.into_sorted_vec_fast(),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+pub fn benchmark_remove_duplicates(c: &mut Criterion) {
+    let num_items_to_insert: usize = black_box(10000);
+    let mut data = vec![7; num_items_to_insert];
+    data.extend(0..10);
+    c.bench_function("benchmark remove_object with duplicates", |b| {
+        b.iter_batched(
+            || BinaryHeap::<i32, fnv::FnvBuildHasher>::heapify(&data, HeapKind::Min),
+            |mut heap| {
+                for _ in 0..num_items_to_insert {
+                    heap.remove_object(&7);
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+// Duplicates spread all over the heap rather than one dominant value, so most removals have a
+// choice between several occurrences.
+pub fn benchmark_remove_spread_duplicates(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let num_items_to_insert: usize = black_box(10000);
+    let data: Vec<i32> = (0..num_items_to_insert)
+        .map(|_| rng.gen_range(0..100))
+        .collect();
+    let targets: Vec<i32> = data.iter().copied().take(num_items_to_insert / 2).collect();
+    c.bench_function("benchmark remove_object with spread duplicates", |b| {
+        b.iter_batched(
+            || BinaryHeap::<i32, fnv::FnvBuildHasher>::heapify(&data, HeapKind::Min),
+            |mut heap| {
+                for target in &targets {
+                    heap.remove_object(target);
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+// Extending a heap of 10000 elements by batches of increasing size, forcing either strategy.
+// The default threshold switches over where the two lines cross.
+pub fn benchmark_extend(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let data: Vec<i32> = (&mut rng).sample_iter(Standard).take(10000).collect();
+    let mut group = c.benchmark_group("benchmark extend");
+    for batch in [100, 1000, 5000, 10000, 20000] {
+        let incoming: Vec<i32> = (&mut rng).sample_iter(Standard).take(batch).collect();
+        for (strategy, ratio) in [("insert", f64::INFINITY), ("rebuild", 0.0)] {
+            group.bench_function(format!("{} {}", strategy, batch), |b| {
+                b.iter_batched(
+                    || {
+                        let mut heap =
+                            BinaryHeap::<i32, fnv::FnvBuildHasher>::heapify(&data, HeapKind::Min);
+                        heap.set_extend_rebuild_threshold(ratio);
+                        heap
+                    },
+                    |mut heap| heap.extend(incoming.iter().copied()),
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+// Creating and filling 100k heaps of a handful of elements each, with and without the initial
+// capacity floor.
+pub fn benchmark_many_small_heaps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("benchmark many small heaps");
+    for (name, min_cap) in [("new", false), ("new_with_min_cap", true)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for _ in 0..100_000 {
+                    let mut heap: BinaryHeap<i32, fnv::FnvBuildHasher> = if min_cap {
+                        BinaryHeap::new_with_min_cap(HeapKind::Min)
+                    } else {
+                        BinaryHeap::new(HeapKind::Min)
+                    };
+                    (0..6).for_each(|item| heap.insert(item).unwrap());
+                    black_box(&heap);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+// An element that is expensive to hash (a long key) but cheap to compare, where hashing on every
+// move would dominate the cost of the heap operations.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+struct SlowHash {
+    priority: i32,
+    key: [u64; 64],
+}
+
+impl std::hash::Hash for SlowHash {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.priority.hash(state);
+        self.key.hash(state);
+    }
+}
+
+pub fn benchmark_slow_hash(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let data: Vec<SlowHash> = (0..10000)
+        .map(|_| SlowHash {
+            priority: rng.gen(),
+            key: [rng.gen(); 64],
+        })
+        .collect();
+    c.bench_function("benchmark slow hash insert and extract", |b| {
+        b.iter(|| {
+            let mut heap =
+                BinaryHeap::<SlowHash, fnv::FnvBuildHasher>::heapify(&data, HeapKind::Min);
+            while heap.extract_object().is_some() {}
+        })
+    });
+}
+
+#[cfg(feature = "rayon")]
+pub fn benchmark_par_heapify(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let num_items_to_insert: usize = black_box(10_000_000);
+    let data: Vec<i32> = (&mut rng)
+        .sample_iter(Standard)
+        .take(num_items_to_insert)
+        .collect();
+    let mut group = c.benchmark_group("benchmark heapify 10M");
+    group.sample_size(10);
+    group.bench_function("heapify", |b| {
+        b.iter(|| BinaryHeap::<i32, fnv::FnvBuildHasher>::heapify(&data, HeapKind::Min))
+    });
+    group.bench_function("par_heapify", |b| {
+        b.iter(|| BinaryHeap::<i32, fnv::FnvBuildHasher>::par_heapify(&data, HeapKind::Min))
+    });
+    group.finish();
+}
+
+#[cfg(not(feature = "rayon"))]
+pub fn benchmark_par_heapify(_: &mut Criterion) {}
+
+criterion_group!(
+    benches,
+    benchmark_insert,
+    benchmark_extract_object,
+    benchmark_extract_small,
+    benchmark_into_sorted_vec,
+    benchmark_remove_duplicates,
+    benchmark_remove_spread_duplicates,
+    benchmark_extend,
+    benchmark_many_small_heaps,
+    benchmark_slow_hash,
+    benchmark_par_heapify
+);
 criterion_main!(benches);
@@ -1,22 +1,250 @@
 #![warn(clippy::all)]
-use ::core::hash::{BuildHasher, Hasher};
+use ::core::hash::BuildHasher;
 use hashbrown::HashMap;
+use std::any::Any;
 use std::collections::hash_map::RandomState;
 use std::collections::VecDeque;
+use std::sync::Arc;
+
+#[cfg(feature = "rayon")]
+mod parallel;
 
 const PARENT_VIOLATION: &str = "PARENT_VIOLATION";
 const CHILDREN_VIOLATION: &str = "CHILDREN_VIOLATION";
+// Debug builds re-check the whole heap after insert/extract/remove only up to this size, the O(n)
+// check per operation would otherwise make debug runs on big heaps crawl.
+const DEBUG_CHECK_LIMIT: usize = 1024;
+// Tables smaller than this are never compacted, dropping a handful of empty buckets isn't worth
+// the reallocation.
+const COMPACTION_MIN_BUCKETS: usize = 64;
+// Starting capacity of new_with_min_cap, enough for a handful of inserts without reallocating.
+pub const DEFAULT_MIN_CAPACITY: usize = 8;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HeapKind {
     Max,
     Min,
 }
 
+impl HeapKind {
+    // Whether obj1 should sit above (or at the same level as) obj2 in a heap of this kind.
+    fn prioritizes<T: std::cmp::PartialOrd>(&self, obj1: &T, obj2: &T) -> bool {
+        match self {
+            HeapKind::Max => obj1 >= obj2,
+            HeapKind::Min => obj1 <= obj2,
+        }
+    }
+
+    // Same as prioritizes, but goes through `total_cmp` when the heap was built for an Ord type.
+    fn prioritizes_with<T: std::cmp::PartialOrd>(
+        &self,
+        total_cmp: Option<TotalCmp<T>>,
+        obj1: &T,
+        obj2: &T,
+    ) -> bool {
+        match total_cmp.map(|cmp| cmp(obj1, obj2)) {
+            Some(ordering) => !matches!(
+                (self, ordering),
+                (HeapKind::Max, std::cmp::Ordering::Less)
+                    | (HeapKind::Min, std::cmp::Ordering::Greater)
+            ),
+            None => self.prioritizes(obj1, obj2),
+        }
+    }
+}
+
+// `Ord::cmp` of the element type, captured by new_ord.
+type TotalCmp<T> = fn(&T, &T) -> std::cmp::Ordering;
+
+// What insert does with a value equal to one already stored.
+#[derive(Debug, Clone)]
+pub enum DuplicatePolicy<T> {
+    // Store another copy, the default.
+    Allow,
+    // Reject the value, checked_insert hands it back.
+    Forbid,
+    // Merge it into the stored copy, see with_coalesce.
+    Coalesce(Merge<T>),
+}
+
+impl<T: 'static> DuplicatePolicy<T> {
+    // Coalesce with a closure, which may capture state (e.g. a counter shared with the caller).
+    pub fn coalesce(merge: impl Fn(&mut T, T) + Send + Sync + 'static) -> Self {
+        DuplicatePolicy::Coalesce(Merge::new(merge))
+    }
+}
+
+type ErasedMerge = dyn Fn(&mut dyn Any, &mut dyn Any) + Send + Sync;
+
+// Folds an inserted value into the equal one already stored, see DuplicatePolicy::coalesce.
+// The closure is kept behind `dyn Any` arguments rather than as a `dyn Fn(&mut T, T)`: a trait
+// object naming T would have to be dropped along with the heap, which stops heaps of borrowed
+// elements (heapify_refs) from outliving whatever they were last compared against. The two
+// plain fns turn the arguments back into `dyn Any`.
+pub struct Merge<T> {
+    merge: Arc<ErasedMerge>,
+    stored_as_any: fn(&mut T) -> &mut dyn Any,
+    inserted_as_any: fn(&mut Option<T>) -> &mut dyn Any,
+}
+
+impl<T: 'static> Merge<T> {
+    fn new(merge: impl Fn(&mut T, T) + Send + Sync + 'static) -> Self {
+        Merge {
+            merge: Arc::new(move |stored: &mut dyn Any, inserted: &mut dyn Any| {
+                let stored = stored.downcast_mut::<T>().unwrap();
+                let inserted = inserted.downcast_mut::<Option<T>>().unwrap();
+                merge(stored, inserted.take().unwrap());
+            }),
+            stored_as_any: |stored| stored,
+            inserted_as_any: |inserted| inserted,
+        }
+    }
+}
+
+impl<T> Merge<T> {
+    fn call(&self, stored: &mut T, inserted: T) {
+        let mut inserted = Some(inserted);
+        (self.merge)(
+            (self.stored_as_any)(stored),
+            (self.inserted_as_any)(&mut inserted),
+        );
+    }
+}
+
+// The root change callback, erased like Merge for the same reason, see set_on_root_change.
+type ErasedCallback = dyn Fn(&dyn Any) + Send + Sync;
+
+struct RootCallback<T> {
+    callback: Arc<ErasedCallback>,
+    as_any: fn(&T) -> &dyn Any,
+}
+
+impl<T> Clone for RootCallback<T> {
+    fn clone(&self) -> Self {
+        RootCallback {
+            callback: Arc::clone(&self.callback),
+            as_any: self.as_any,
+        }
+    }
+}
+
+// Not derived, that would needlessly require T: Clone.
+impl<T> Clone for Merge<T> {
+    fn clone(&self) -> Self {
+        Merge {
+            merge: Arc::clone(&self.merge),
+            stored_as_any: self.stored_as_any,
+            inserted_as_any: self.inserted_as_any,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Merge<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Merge")
+    }
+}
+
+// How the element storage grows once it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthPolicy {
+    // std's amortized doubling
+    Doubling,
+    // Grow by a fixed number of slots, trading throughput for smaller allocation spikes.
+    Linear(usize),
+}
+
+// Returned by insert when the heap is at its hard capacity, handing the object back.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CapacityExceeded<T>(pub T);
+
+impl<T> std::fmt::Display for CapacityExceeded<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "heap is at its hard capacity")
+    }
+}
+
+impl<T: std::fmt::Debug> std::error::Error for CapacityExceeded<T> {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InsertResult<T> {
+    Accepted,
+    // The heap was full and the object didn't beat its lowest priority element.
+    Rejected(T),
+    // The object was accepted, evicting the returned lowest priority element.
+    Evicted(T),
+}
+
 pub struct BinaryHeap<T, S = RandomState> {
     elements: VecDeque<T>,
+    // Insertion sequence number of every element, kept in lockstep with `elements`.
+    // Used to break ties between equal priority elements in stable heaps.
+    insertion_order: VecDeque<u64>,
+    next_insertion: u64,
+    // Hash of every element, also kept in lockstep with `elements`, so moving an element around
+    // never hashes it again. Only recomputed when a value changes or the table gets rebuilt.
+    hashes: VecDeque<u64>,
     kind: HeapKind,
     element_indices: HashMap<u64, Vec<usize>>,
     hash_builder: S,
+    stable: bool,
+    bound: Option<usize>,
+    hard_capacity: Option<usize>,
+    // Bumped on every mutation, see CheckedIndex.
+    version: u64,
+    growth_policy: GrowthPolicy,
+    // See auto_shrink.
+    shrink_ratio: Option<f64>,
+    // Set by new_ord. When present every comparison goes through it instead of PartialOrd.
+    total_cmp: Option<TotalCmp<T>>,
+    // See set_extend_rebuild_threshold.
+    extend_rebuild_ratio: f64,
+    duplicate_policy: DuplicatePolicy<T>,
+    // Table entries left without any index. They are kept around for reinsertion of the same
+    // value, until remove_object decides to compact the table (see set_compaction_threshold).
+    empty_buckets: usize,
+    compaction_ratio: f64,
+    // See set_on_root_change.
+    on_root_change: Option<RootCallback<T>>,
+}
+
+// An index paired with the heap version it was taken at. Resolving it fails once the heap has been
+// mutated since, instead of silently pointing at whatever element moved into that position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckedIndex {
+    pub index: usize,
+    pub version: u64,
+}
+
+// Returned when adopting a Vec that doesn't satisfy the heap property. `index` is where the
+// property first breaks (as reported by first_violation) and `elements` hands the Vec back.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HeapViolation<T> {
+    pub index: usize,
+    pub elements: Vec<T>,
+}
+
+// An element together with the values directly above and below it in the tree.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Neighbors<'a, T> {
+    pub element: &'a T,
+    pub parent: Option<&'a T>,
+    pub children: [Option<&'a T>; 2],
+}
+
+// Rough number of bytes held by a heap, see BinaryHeap::memory_footprint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub elements: usize,
+    pub insertion_order: usize,
+    pub hashes: usize,
+    pub index_table: usize,
+}
+
+impl MemoryReport {
+    pub fn total(&self) -> usize {
+        self.elements + self.insertion_order + self.hashes + self.index_table
+    }
 }
 
 impl<T, S> BinaryHeap<T, S>
@@ -25,76 +253,1034 @@ where
     S: BuildHasher + Default,
 {
     pub fn new_with_capacity(heap_type: HeapKind, elements_size: usize) -> Self {
-        Self {
-            elements: VecDeque::with_capacity(elements_size),
-            kind: heap_type,
-            element_indices: HashMap::with_capacity(elements_size),
-            hash_builder: S::default(),
-        }
+        Self::with_full(elements_size, heap_type, S::default())
     }
 
     pub fn heapify(items: &[T], kind: HeapKind) -> Self {
         items.iter().fold(
             Self::new_with_capacity(kind, items.len()),
             |mut acc, item| {
-                acc.insert(item.clone());
+                acc.insert_uncapped(item.clone());
+                acc
+            },
+        )
+    }
+
+    // Takes ownership of `items`, builds the heap in O(n) and also reports how many of them were
+    // duplicates of an earlier item (total minus distinct).
+    pub fn from_vec_counting(items: Vec<T>, kind: HeapKind) -> (Self, usize) {
+        let mut heap = Self::new_with_capacity(kind, items.len());
+        heap.extend(items);
+        let duplicates = heap.count_total() - heap.count_distinct();
+        (heap, duplicates)
+    }
+
+    // An accumulating heap: inserting a value equal to one already stored calls
+    // `merge(&mut stored, inserted)` and re-sifts the stored element instead of adding a copy.
+    pub fn with_coalesce(kind: HeapKind, merge: impl Fn(&mut T, T) + Send + Sync + 'static) -> Self
+    where
+        T: 'static,
+    {
+        Self::with_duplicate_policy(kind, DuplicatePolicy::coalesce(merge))
+    }
+
+    pub fn with_duplicate_policy(kind: HeapKind, duplicate_policy: DuplicatePolicy<T>) -> Self {
+        let mut heap = Self::new(kind);
+        heap.duplicate_policy = duplicate_policy;
+        heap
+    }
+
+    // Builds a multiset heap holding `count` copies of every value, bottom up in O(total). Each
+    // value is hashed once rather than once per copy.
+    pub fn from_counts(counts: impl IntoIterator<Item = (T, usize)>, kind: HeapKind) -> Self {
+        let mut heap = Self::new(kind);
+        for (value, count) in counts.into_iter().filter(|(_, count)| *count > 0) {
+            let hash_value = Self::hash_value(&heap.hash_builder, &value);
+            heap.hashes.extend(std::iter::repeat_n(hash_value, count));
+            heap.insertion_order.extend(std::iter::repeat_n(0, count));
+            heap.elements.extend(std::iter::repeat_n(value, count));
+        }
+        for ind in (0..heap.len() / 2).rev() {
+            heap.sift_down_untracked(ind);
+        }
+        for ind in 0..heap.len() {
+            heap.update_table_for_element_entry(ind);
+            heap.insertion_order[ind] = ind as u64;
+        }
+        heap.next_insertion = heap.len() as u64;
+        heap
+    }
+
+    // Builds a heap of references into `items`, so nothing but the pointers gets cloned.
+    // Hashing and equality of a `&T` go through the pointee, so `remove_object` still works by value.
+    pub fn heapify_refs(items: &[T], kind: HeapKind) -> BinaryHeap<&T, S> {
+        items.iter().fold(
+            BinaryHeap::new_with_capacity(kind, items.len()),
+            |mut acc, item| {
+                acc.insert_uncapped(item);
                 acc
             },
         )
     }
 
+    // In place heap sort: builds a heap of `kind` inside `items` and repeatedly swaps the root to the
+    // end. A Max heap thus sorts ascending and a Min heap descending. Nothing is allocated.
+    pub fn sort(items: &mut [T], kind: HeapKind) {
+        for ind in (0..items.len() / 2).rev() {
+            sift_down_slice(items, ind, &kind);
+        }
+        for end in (1..items.len()).rev() {
+            items.swap(0, end);
+            sift_down_slice(&mut items[..end], 0, &kind);
+        }
+    }
+
+    // Elements are compared through PartialOrd. If some of them are incomparable (partial_cmp
+    // returning None) they count as lower priority both ways around, so the heap property can't
+    // hold for them: release builds extract them in no particular order and debug builds panic in
+    // their consistency checks. Prefer new_ord for types that are Ord.
     pub fn new(heap_type: HeapKind) -> Self {
         Self::new_with_capacity(heap_type, 0)
     }
+
+    // Streams `items` through a heap of the opposite kind holding the k best seen so far, so its
+    // root is always the one to beat and the input is never collected. O(n log k), plus an O(k)
+    // rebuild into `kind` at the end.
+    pub fn top_k_from(items: impl IntoIterator<Item = T>, k: usize, kind: HeapKind) -> Self {
+        let opposite = match kind {
+            HeapKind::Max => HeapKind::Min,
+            HeapKind::Min => HeapKind::Max,
+        };
+        let mut best = Self::new_with_capacity(opposite, k);
+        for item in items {
+            if best.len() < k {
+                best.insert_uncapped(item);
+            } else if best
+                .peek()
+                .is_some_and(|worst| !kind.prioritizes(worst, &item))
+            {
+                best.extract_object();
+                best.insert_uncapped(item);
+            }
+        }
+        best.rebuild_as(kind);
+        best
+    }
+
+    // Unlike new, allocates room for DEFAULT_MIN_CAPACITY elements up front, which saves the
+    // 0 -> 1 -> 2 -> 4 reallocations of many short lived, small heaps.
+    pub fn new_with_min_cap(heap_type: HeapKind) -> Self {
+        Self::new_with_capacity(heap_type, DEFAULT_MIN_CAPACITY)
+    }
+}
+
+impl<T, S> BinaryHeap<T, S>
+where
+    T: Ord + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher + Default,
+{
+    /// Builds a heap that orders elements with `Ord::cmp`, so the order is total and the heap stays
+    /// correct no matter how `PartialOrd` is implemented. Heaps from `new` compare through
+    /// `PartialOrd` and can end up misordered if some elements are incomparable.
+    ///
+    /// ```
+    /// use binary_heap::{BinaryHeap, HeapKind};
+    ///
+    /// let mut heap = BinaryHeap::<u32>::new_ord(HeapKind::Min);
+    /// heap.insert(3).unwrap();
+    /// heap.insert(1).unwrap();
+    /// assert_eq!(heap.extract_object(), Some(1));
+    /// ```
+    ///
+    /// Types that are only `PartialOrd` can still use `new`, but not `new_ord`:
+    ///
+    /// ```compile_fail
+    /// use binary_heap::{BinaryHeap, HeapKind};
+    ///
+    /// #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd)]
+    /// struct Reading(u32);
+    ///
+    /// let heap = BinaryHeap::<Reading>::new_ord(HeapKind::Min);
+    /// ```
+    pub fn new_ord(heap_type: HeapKind) -> Self {
+        let mut heap = Self::new(heap_type);
+        heap.total_cmp = Some(T::cmp);
+        heap
+    }
+}
+
+impl<T, S> BinaryHeap<T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    // The lowest level constructor, every other one delegates to it. Since the hasher instance is
+    // passed in, S doesn't have to implement Default.
+    pub fn with_full(elements_size: usize, heap_type: HeapKind, hash_builder: S) -> Self {
+        Self {
+            elements: VecDeque::with_capacity(elements_size),
+            insertion_order: VecDeque::with_capacity(elements_size),
+            next_insertion: 0,
+            hashes: VecDeque::with_capacity(elements_size),
+            kind: heap_type,
+            element_indices: HashMap::with_capacity(elements_size),
+            hash_builder,
+            stable: false,
+            bound: None,
+            hard_capacity: None,
+            version: 0,
+            growth_policy: GrowthPolicy::Doubling,
+            shrink_ratio: None,
+            total_cmp: None,
+            extend_rebuild_ratio: 1.0,
+            duplicate_policy: DuplicatePolicy::Allow,
+            empty_buckets: 0,
+            compaction_ratio: 0.75,
+            on_root_change: None,
+        }
+    }
+
+    pub(crate) fn with_options(
+        heap_type: HeapKind,
+        elements_size: usize,
+        hash_builder: S,
+        stable: bool,
+        bound: Option<usize>,
+    ) -> Self {
+        let mut heap = Self::with_full(elements_size, heap_type, hash_builder);
+        heap.stable = stable;
+        heap.bound = bound;
+        heap
+    }
+
     // O(log n)
-    pub fn insert(&mut self, object: T) {
-        self.push_back(object);
-        let currently_inserted_index = self.elements.len() - 1;
+    // A bounded heap that is full only takes `object` if it beats the current lowest priority
+    // element, which then gets evicted. Otherwise `object` is dropped.
+    // Fails, handing `object` back, only if a hard capacity is set and reached.
+    pub fn insert(&mut self, object: T) -> Result<(), CapacityExceeded<T>> {
+        self.insert_within_capacity(object).map(|_| ())
+    }
+
+    // insert for heaps that can't have a hard capacity, e.g. ones just built by the constructors.
+    pub(crate) fn insert_uncapped(&mut self, object: T) {
+        debug_assert!(self.hard_capacity.is_none());
+        let _ = self.insert_within_capacity(object);
+    }
+
+    // Same as insert, but reports what happened to `object` and hands back whichever element
+    // didn't make it into a full bounded heap (or a heap at its hard capacity).
+    pub fn checked_insert(&mut self, object: T) -> InsertResult<T> {
+        match self.insert_within_capacity(object) {
+            Ok(result) => result,
+            Err(CapacityExceeded(object)) => InsertResult::Rejected(object),
+        }
+    }
+
+    // Unlike bound, which makes room by evicting, a hard capacity refuses any insert that would
+    // grow the heap past `cap` elements. insert and try_extend report that as CapacityExceeded,
+    // but `extend` (the Extend impl) has no way to and panics, so use try_extend instead.
+    pub fn set_hard_capacity(&mut self, cap: usize) {
+        self.hard_capacity = Some(cap);
+    }
+
+    // Fallible extend: inserts every item that still fits and hands back the ones that didn't, in
+    // input order. Only a hard capacity can make it fail.
+    pub fn try_extend(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+    ) -> Result<(), CapacityExceeded<Vec<T>>> {
+        if self.hard_capacity.is_none() {
+            self.extend(items);
+            return Ok(());
+        }
+        let rejected: Vec<T> = items
+            .into_iter()
+            .filter_map(|item| self.insert(item).err())
+            .map(|CapacityExceeded(item)| item)
+            .collect();
+        if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(CapacityExceeded(rejected))
+        }
+    }
+
+    fn insert_within_capacity(
+        &mut self,
+        object: T,
+    ) -> Result<InsertResult<T>, CapacityExceeded<T>> {
+        let root = self.root_insertion();
+        if let Some(index) = self.stored_duplicate(&object) {
+            let merge = match &self.duplicate_policy {
+                DuplicatePolicy::Coalesce(merge) => merge.clone(),
+                _ => return Ok(InsertResult::Rejected(object)),
+            };
+            let merged = self.insertion_order[index];
+            self.remove_from_table(index, index);
+            self.bump_version();
+            merge.call(&mut self.elements[index], object);
+            self.rehash(index);
+            self.update_table_for_element_entry(index);
+            let res = self.check_heap_invariants_at(index);
+            self.ensure_heap_invariants(res, index);
+            self.debug_assert_consistent();
+            self.notify_root_update(root, merged);
+            return Ok(InsertResult::Accepted);
+        }
+        let result = self.insert_untracked_root(object);
+        self.notify_root_change(root);
+        result
+    }
+
+    // Where an equal copy of `object` is stored, if the duplicate policy cares. The table is keyed
+    // by hash, so a colliding bucket may hold other values.
+    fn stored_duplicate(&self, object: &T) -> Option<usize> {
+        if let DuplicatePolicy::Allow = self.duplicate_policy {
+            return None;
+        }
+        self.occurrences(object)
+            .find(|(_, stored)| *stored == object)
+            .map(|(index, _)| index)
+    }
+
+    fn insert_untracked_root(&mut self, object: T) -> Result<InsertResult<T>, CapacityExceeded<T>> {
+        let mut evicted = None;
+        if let Some(bound) = self.bound {
+            if self.len() >= bound {
+                match self.worst_leaf_index() {
+                    Some(worst_ind)
+                        if !self.verify_priority(self.element_at(worst_ind).unwrap(), &object) =>
+                    {
+                        evicted = self.remove_at(worst_ind);
+                    }
+                    _ => return Ok(InsertResult::Rejected(object)),
+                }
+            }
+        }
+        if evicted.is_none() && self.hard_capacity.is_some_and(|cap| self.len() >= cap) {
+            return Err(CapacityExceeded(object));
+        }
+        let currently_inserted_index = self.push_back(object);
         self.bubble_up(currently_inserted_index);
+        self.debug_assert_consistent();
+        Ok(match evicted {
+            Some(evicted_object) => InsertResult::Evicted(evicted_object),
+            None => InsertResult::Accepted,
+        })
     }
 
     // Extract the highest_priority object from the heap
     // O(log n)
+    // The table is kept in sync with `elements` after every step: the root is swapped to the back,
+    // popped along with its table entry and the new root is then sifted down.
+    // No element is dropped in here: the root is only moved out, and is dropped by the caller once
+    // the heap is consistent again. So a Drop impl that goes back to the same heap (e.g. through a
+    // RefCell, the only way to reach it while this holds &mut self) sees a valid heap. Methods that
+    // discard elements themselves drop them as their very last step, so a panicking Drop can't
+    // leave the heap half updated either.
     pub fn extract_object(&mut self) -> Option<T> {
-        self.handle_table_changes();
-        let max_priority_elem = self.elements.pop_front();
-        match self.elements.pop_back() {
-            Some(last_entry) => {
-                self.push_front(last_entry);
+        let root = self.root_insertion();
+        let max_priority_elem = self.extract_root();
+        self.notify_root_change(root);
+        max_priority_elem
+    }
+
+    // extract_object without the root change notification, for callers that notify themselves.
+    fn extract_root(&mut self) -> Option<T> {
+        let max_priority_elem = match self.len() {
+            0 => return None,
+            // Nothing to move around, only the root's table entry goes.
+            1 => self.pop_back_tracked(),
+            // The other element becomes the root as is, no swap or sift needed.
+            2 => {
+                self.remove_from_table(0, 0);
+                self.remove_from_table(1, 1);
+                self.bump_version();
+                self.insertion_order.pop_front();
+                self.hashes.pop_front();
+                let root = self.elements.pop_front();
+                self.update_table_for_element_entry(0);
+                root
+            }
+            len => {
+                self.swap_elements(0, len - 1);
+                let root = self.pop_back_tracked();
                 self.bubble_down(0);
-                max_priority_elem
+                root
             }
-            None => max_priority_elem,
+        };
+        self.shrink_if_sparse();
+        self.debug_assert_consistent();
+        max_priority_elem
+    }
+
+    // Gives mutable access to every element. Values (and so their hashes) may change arbitrarily, so
+    // the heap is left inconsistent until the returned guard is dropped, at which point the heap and
+    // the index table get rebuilt in O(n). Iterate with `for item in &mut heap.iter_mut() { .. }`.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, S> {
+        IterMut { heap: self }
+    }
+
+    // Like iter_mut, but hands out the storage as one `&mut [T]`, e.g. to sort it or run slice
+    // algorithms over it. Elements can be moved as well as changed, and the guard rebuilds the
+    // heap and the index table in O(n) when dropped. For a few changes at known positions,
+    // fix_at is cheaper.
+    pub fn elements_mut(&mut self) -> ElementsMut<'_, T, S> {
+        self.elements.make_contiguous();
+        ElementsMut { heap: self }
+    }
+
+    // The storage as a plain `&mut [T]` with nothing rebuilt afterwards, for heap algorithms
+    // implemented outside the crate. The contract: only swap elements through it, then report all
+    // of the swaps, in order, to notify_swapped before using the heap again. Restoring the heap
+    // property is up to the caller (fix_at and ensure_valid can help). Any other change leaves
+    // the index table wrong; use elements_mut for those.
+    pub fn raw_elements_mut(&mut self) -> &mut [T] {
+        self.elements.make_contiguous()
+    }
+
+    // Builds a heap of the same kind (and configuration) over `f` applied to every element.
+    // The transform can change the ordering, so the new heap is rebuilt from scratch in O(n).
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> BinaryHeap<U, S>
+    where
+        U: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    {
+        let mut mapped = BinaryHeap {
+            elements: self.elements.into_iter().map(f).collect(),
+            insertion_order: self.insertion_order,
+            next_insertion: self.next_insertion,
+            hashes: VecDeque::new(),
+            kind: self.kind,
+            element_indices: HashMap::with_capacity(self.element_indices.len()),
+            hash_builder: self.hash_builder,
+            stable: self.stable,
+            bound: self.bound,
+            hard_capacity: self.hard_capacity,
+            version: self.version,
+            growth_policy: self.growth_policy,
+            shrink_ratio: self.shrink_ratio,
+            total_cmp: None,
+            extend_rebuild_ratio: self.extend_rebuild_ratio,
+            duplicate_policy: DuplicatePolicy::Allow,
+            empty_buckets: 0,
+            compaction_ratio: self.compaction_ratio,
+            on_root_change: None,
+        };
+        mapped.rebuild();
+        mapped
+    }
+
+    // Splits the heap into a (Min, Max) pair over the same elements. The copy whose kind matches
+    // the original is kept as is, the other one is rebuilt in O(n).
+    pub fn into_ordered_pair(self) -> (Self, Self)
+    where
+        S: Clone,
+    {
+        let original_kind = self.kind;
+        let mut min = BinaryHeap {
+            elements: self.elements.clone(),
+            insertion_order: self.insertion_order.clone(),
+            next_insertion: self.next_insertion,
+            hashes: self.hashes.clone(),
+            kind: HeapKind::Min,
+            element_indices: self.element_indices.clone(),
+            hash_builder: self.hash_builder.clone(),
+            stable: self.stable,
+            bound: self.bound,
+            hard_capacity: self.hard_capacity,
+            version: self.version,
+            growth_policy: self.growth_policy,
+            shrink_ratio: self.shrink_ratio,
+            total_cmp: self.total_cmp,
+            extend_rebuild_ratio: self.extend_rebuild_ratio,
+            duplicate_policy: self.duplicate_policy.clone(),
+            empty_buckets: self.empty_buckets,
+            compaction_ratio: self.compaction_ratio,
+            on_root_change: self.on_root_change.clone(),
+        };
+        let mut max = self;
+        max.kind = HeapKind::Max;
+        match original_kind {
+            HeapKind::Min => max.rebuild(),
+            HeapKind::Max => min.rebuild(),
         }
+        (min, max)
     }
 
-    pub fn remove_object(&mut self, object: &T) -> Option<T> {
-        if let Some(present_indices) = self.get_index(object) {
-            let index_to_remove = present_indices[0];
-            let last_element_index = self.len() - 1;
-            // If the element to be removed is the first element in the vector, then we simply call extract_object().
-            // On the otherhand, if the element is the last element in the vector, we remove the element's index entry from the table
-            // and then call pop_back on the vector.
-            match index_to_remove {
-                0 => self.extract_object(),
-                x if x == last_element_index => {
-                    self.remove_from_table(last_element_index, last_element_index);
-                    self.elements.pop_back()
+    // Replaces every element with `f(element)`, then rebuilds the heap and the index table (the new
+    // values hash differently) in a single O(n) pass. The bulk version of update.
+    pub fn transform_all(&mut self, f: impl Fn(&T) -> T) {
+        let transformed = self.elements.iter().map(f).collect();
+        let originals = std::mem::replace(&mut self.elements, transformed);
+        self.rebuild();
+        drop(originals);
+    }
+
+    // Like transform_all for transforms that can work on the element in place, so nothing gets
+    // reallocated. The heap and index table are rebuilt once afterwards.
+    pub fn map_in_place(&mut self, f: impl FnMut(&mut T)) {
+        self.elements.iter_mut().for_each(f);
+        self.rebuild();
+    }
+
+    // Extracts the lowest priority element instead of the root. It has to be one of the leaves, so
+    // this scans the leaf range in O(n).
+    pub fn extract_worst(&mut self) -> Option<T> {
+        let worst = self.worst_leaf_index()?;
+        self.remove_at(worst)
+    }
+
+    // Removes the highest and the lowest priority elements together, as (best, worst). A single
+    // element is both, so heaps of fewer than two elements return None and are left untouched.
+    pub fn extract_extremes(&mut self) -> Option<(T, T)> {
+        if self.len() < 2 {
+            return None;
+        }
+        let root = self.root_insertion();
+        // The worst element is a leaf, taking it out first never disturbs the root.
+        let worst_index = self.worst_leaf_index()?;
+        let worst = self.remove_at(worst_index)?;
+        let best = self.extract_root()?;
+        self.notify_root_change(root);
+        Some((best, worst))
+    }
+
+    // Yields the elements lowest priority first, one extract_worst at a time. The heap stays
+    // valid after every step, so dropping the iterator early leaves the rest queued.
+    pub fn drain_sorted_rev(&mut self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(move || self.extract_worst())
+    }
+
+    // Keep extracting the root for as long as `pred` holds on it, returning the drained batch
+    // in priority order.
+    pub fn extract_while_root(&mut self, mut pred: impl FnMut(&T) -> bool) -> Vec<T> {
+        let mut batch = Vec::new();
+        while self.peek().is_some_and(&mut pred) {
+            batch.extend(self.extract_object());
+        }
+        batch
+    }
+
+    // Unlike Vec::truncate this drops by priority: only the `len` highest priority elements are kept.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len() {
+            return;
+        }
+        let root = self.root_insertion();
+        let mut kept = VecDeque::with_capacity(len);
+        let mut insertions = VecDeque::with_capacity(len);
+        while kept.len() < len {
+            insertions.push_back(self.insertion_order[0]);
+            kept.extend(self.extract_root());
+        }
+        // Elements in priority order already satisfy the heap property, only the table needs rebuilding.
+        let shed = std::mem::replace(&mut self.elements, kept);
+        self.insertion_order = insertions;
+        self.rebuild_table();
+        self.notify_root_change(root);
+        // Dropped only once the heap is consistent again, see extract_object.
+        drop(shed);
+    }
+
+    // Like truncate, but hands back the (len - target_len) lowest priority elements it shed, in
+    // priority order. The elements to keep are picked with top_indices, then everything else is
+    // moved out with a single rebuild.
+    pub fn shed_to(&mut self, target_len: usize) -> Vec<T> {
+        if target_len >= self.len() {
+            return vec![];
+        }
+        let kept: std::collections::HashSet<usize> =
+            self.top_indices(target_len).into_iter().collect();
+        let mut shed: Vec<T> = self
+            .extract_matching(|ind, _| !kept.contains(&ind))
+            .into_iter()
+            .map(|(element, _)| element)
+            .collect();
+        shed.sort_by(|obj1, obj2| self.priority_ordering(obj1, obj2));
+        shed
+    }
+
+    // Removes every element with lo <= x <= hi (both bounds inclusive) and returns them in priority
+    // order. The heap isn't a search tree so this scans all elements, then rebuilds the rest once.
+    pub fn extract_range(&mut self, lo: &T, hi: &T) -> Vec<T> {
+        let mut extracted: Vec<T> = self
+            .extract_matching(|_, item| lo <= item && item <= hi)
+            .into_iter()
+            .map(|(element, _)| element)
+            .collect();
+        extracted.sort_by(|obj1, obj2| self.priority_ordering(obj1, obj2));
+        extracted
+    }
+
+    // Read only counterpart of extract_range: every element with lo <= x <= hi, in storage order.
+    // The bounds are cloned, so they only need to live for the call.
+    pub fn iter_range<'a>(&'a self, lo: &T, hi: &T) -> impl Iterator<Item = &'a T> + 'a {
+        let (lo, hi) = (lo.clone(), hi.clone());
+        self.elements
+            .iter()
+            .filter(move |item| lo <= **item && **item <= hi)
+    }
+
+    // Counts the elements falling into each of `buckets` equal width intervals splitting [lo, hi].
+    // Every interval is closed at the bottom and open at the top, except the last one which also
+    // takes `hi`. Elements outside [lo, hi] are ignored rather than clamped.
+    pub fn histogram(&self, buckets: usize, lo: &T, hi: &T) -> Vec<usize>
+    where
+        T: Copy + Into<f64>,
+    {
+        let mut counts = vec![0; buckets];
+        let (lo, hi): (f64, f64) = ((*lo).into(), (*hi).into());
+        if buckets == 0 || lo > hi {
+            return counts;
+        }
+        let width = (hi - lo) / buckets as f64;
+        for item in self.elements.iter().map(|item| (*item).into()) {
+            if item < lo || item > hi {
+                continue;
+            }
+            let bucket = if width > 0.0 {
+                ((item - lo) / width) as usize
+            } else {
+                0
+            };
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+        counts
+    }
+
+    // Low level removal for bulk operations: swaps the element at `index` with the last one and
+    // pops it, keeping the index table in sync but deliberately NOT sifting the moved element.
+    // The heap may be invalid afterwards until it is rebuilt, e.g. with rebuild_as(kind).
+    pub fn raw_swap_remove(&mut self, index: usize) -> Option<T> {
+        let last_element_index = self.len().checked_sub(1)?;
+        if index > last_element_index {
+            return None;
+        }
+        self.swap_elements(index, last_element_index);
+        self.pop_back_tracked()
+    }
+
+    // Moves every element for which `pred(index, element)` holds (along with its insertion sequence
+    // number) out of the heap and rebuilds whatever is left in O(n).
+    fn extract_matching(&mut self, mut pred: impl FnMut(usize, &T) -> bool) -> Vec<(T, u64)> {
+        let elements = std::mem::take(&mut self.elements);
+        let insertion_order = std::mem::take(&mut self.insertion_order);
+        let mut matching = vec![];
+        for (ind, (element, insertion)) in elements.into_iter().zip(insertion_order).enumerate() {
+            if pred(ind, &element) {
+                matching.push((element, insertion));
+            } else {
+                self.elements.push_back(element);
+                self.insertion_order.push_back(insertion);
+            }
+        }
+        self.rebuild();
+        matching
+    }
+
+    // Moves every element with strictly lower priority than `at` into the returned heap, `self`
+    // keeps the rest. Elements with the same priority as `at` stay in `self`, so for a Min heap split
+    // at 5 `self` ends up with everything <= 5 and the returned heap with everything > 5.
+    // The returned heap takes over all of `self`'s settings, the root change callback included,
+    // but gets a fresh `S::default()` hasher. Both heaps are rebuilt in O(n).
+    pub fn split_off(&mut self, at: &T) -> Self
+    where
+        S: Default,
+    {
+        let (kind, total_cmp) = (self.kind, self.total_cmp);
+        let worse = self.extract_matching(|_, item| !kind.prioritizes_with(total_cmp, item, at));
+        let mut split = Self::with_options(
+            self.kind,
+            worse.len(),
+            S::default(),
+            self.stable,
+            self.bound,
+        );
+        split.total_cmp = self.total_cmp;
+        split.duplicate_policy = self.duplicate_policy.clone();
+        split.hard_capacity = self.hard_capacity;
+        split.growth_policy = self.growth_policy;
+        split.shrink_ratio = self.shrink_ratio;
+        split.extend_rebuild_ratio = self.extend_rebuild_ratio;
+        split.compaction_ratio = self.compaction_ratio;
+        split.on_root_change = self.on_root_change.clone();
+        for (element, insertion) in worse {
+            split.elements.push_back(element);
+            split.insertion_order.push_back(insertion);
+        }
+        split.next_insertion = self.next_insertion;
+        split.rebuild();
+        split
+    }
+
+    // Extracts a full batch of `batch` elements in priority order, or nothing at all (leaving the
+    // heap untouched) if fewer than that are queued.
+    pub fn extract_n_if_full(&mut self, batch: usize) -> Option<Vec<T>> {
+        if self.len() < batch {
+            return None;
+        }
+        Some((0..batch).filter_map(|_| self.extract_object()).collect())
+    }
+
+    // Removes every copy of every value in `values` with a single rebuild, instead of one
+    // remove_object call (and sift) per copy. Returns how many elements were removed.
+    pub fn bulk_remove<H: BuildHasher>(
+        &mut self,
+        values: &std::collections::HashSet<T, H>,
+    ) -> usize {
+        if values.is_empty() {
+            return 0;
+        }
+        self.extract_matching(|_, item| values.contains(item)).len()
+    }
+
+    // Extracts up to `n` elements and lets `commit` decide whether to keep them. If it returns
+    // false every element goes back in with its original insertion sequence number, so even a
+    // stable heap extracts exactly as it would have before, and None is returned.
+    pub fn extract_transaction(
+        &mut self,
+        n: usize,
+        commit: impl FnOnce(&[T]) -> bool,
+    ) -> Option<Vec<T>> {
+        let root = self.root_insertion();
+        let mut batch = Vec::with_capacity(n.min(self.len()));
+        let mut insertions = Vec::with_capacity(batch.capacity());
+        while batch.len() < n && !self.is_empty() {
+            insertions.push(self.insertion_order[0]);
+            batch.push(self.extract_root().unwrap());
+        }
+        if commit(&batch) {
+            self.notify_root_change(root);
+            return Some(batch);
+        }
+        for (element, insertion) in batch.into_iter().zip(insertions) {
+            let index = self.push_back(element);
+            self.insertion_order[index] = insertion;
+            self.bubble_up(index);
+        }
+        // Everything gets its original insertion number back, but unless the heap is stable an
+        // equal priority element may have ended up at the root in place of the original one.
+        self.notify_root_change(root);
+        None
+    }
+
+    // Switches the heap to `kind`, heapifying bottom up in O(n) under the new ordering and
+    // rebuilding the index table. Debug builds validate the result.
+    pub fn rebuild_as(&mut self, kind: HeapKind) {
+        self.kind = kind;
+        self.rebuild();
+        debug_assert!(self.validate());
+    }
+
+    // Whether any value is stored more than once. Only table buckets holding several indices can
+    // contain copies (empty buckets left behind by removals never do), and since distinct values
+    // can share a hash their elements are compared.
+    pub fn has_duplicates(&self) -> bool {
+        self.element_indices
+            .values()
+            .filter(|indices| indices.len() > 1)
+            .any(|indices| {
+                indices.iter().enumerate().any(|(n, ind)| {
+                    indices[n + 1..]
+                        .iter()
+                        .any(|other| self.elements[*ind] == self.elements[*other])
+                })
+            })
+    }
+
+    // Drops all but one copy of every value, turning a multiset heap into a set heap.
+    // The duplicates are found through the index table, then the heap is rebuilt once.
+    pub fn dedup(&mut self) {
+        let mut duplicate_indices = std::collections::HashSet::new();
+        for indices in self.element_indices.values() {
+            // Distinct values can share a hash, so only drop copies of a value already kept.
+            let mut kept: Vec<&T> = vec![];
+            for ind in indices {
+                let element = self.element_at(*ind).unwrap();
+                if kept.contains(&element) {
+                    duplicate_indices.insert(*ind);
+                } else {
+                    kept.push(element);
                 }
-                _ => {
-                    self.swap_elements(index_to_remove, last_element_index);
-                    self.remove_from_table(last_element_index, last_element_index);
-                    let removed_element = self.elements.pop_back();
-                    let res = self.check_heap_invariants_at(
-                        index_to_remove,
-                        self.element_at(index_to_remove).unwrap(),
-                    );
-                    self.ensure_heap_invariants(res, index_to_remove);
-                    removed_element
+            }
+        }
+        if !duplicate_indices.is_empty() {
+            self.extract_matching(|ind, _| duplicate_indices.contains(&ind));
+        }
+    }
+
+    // Inserts every value of `items` that isn't in the heap yet, once, and rebuilds a single time
+    // afterwards. Builds a set heap out of an iterator with duplicates.
+    // At a hard capacity this stops at the first value that doesn't fit and hands it back, the
+    // ones inserted before it stay.
+    pub fn insert_all_unique(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+    ) -> Result<(), CapacityExceeded<T>> {
+        // Kept in input order, so stable heaps number equal priorities as extend would and bounded
+        // heaps reject the same values on every run. The set only tells what was seen already.
+        let mut seen = std::collections::HashSet::new();
+        let mut batch = vec![];
+        for item in items {
+            if !self.occurrences(&item).any(|(_, present)| *present == item)
+                && seen.insert(item.clone())
+            {
+                batch.push(item);
+            }
+        }
+        // Like extend, bounded heaps have to look at every element as it comes in.
+        if self.bound.is_some() || self.hard_capacity.is_some() {
+            return batch.into_iter().try_for_each(|item| self.insert(item));
+        }
+        if !batch.is_empty() {
+            let incoming = batch.len();
+            self.append_and_rebuild(batch, incoming);
+        }
+        Ok(())
+    }
+
+    // Consumes the heap, returning its elements in the order they would have been extracted.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(item) = self.extract_object() {
+            sorted.push(item);
+        }
+        sorted
+    }
+
+    // Consumes the heap, folding its elements into `init` in priority order.
+    pub fn fold_sorted<B>(mut self, init: B, mut f: impl FnMut(B, T) -> B) -> B {
+        let mut acc = init;
+        while let Some(item) = self.extract_object() {
+            acc = f(acc, item);
+        }
+        acc
+    }
+
+    // Like into_sorted_vec, but appends to a caller owned buffer so its allocation can be reused
+    // across batches. The heap is left empty.
+    pub fn drain_sorted_into(&mut self, out: &mut Vec<T>) {
+        out.reserve(self.len());
+        while let Some(item) = self.extract_object() {
+            out.push(item);
+        }
+    }
+
+    // Merges the heap's extraction order into `sorted_out`, which must already be sorted the same
+    // way the heap extracts (ascending for a Min heap, descending for a Max heap). The combined
+    // result stays sorted without another sort pass; on ties the existing elements come first.
+    pub fn merge_sorted_into(mut self, sorted_out: &mut Vec<T>) {
+        let existing = std::mem::replace(
+            sorted_out,
+            Vec::with_capacity(sorted_out.len() + self.len()),
+        );
+        let mut existing = existing.into_iter().peekable();
+        while let Some(root) = self.peek() {
+            match existing.peek() {
+                Some(next) if self.priority_ordering(next, root) != std::cmp::Ordering::Greater => {
+                    sorted_out.push(existing.next().unwrap());
                 }
+                _ => sorted_out.push(self.extract_object().unwrap()),
+            }
+        }
+        sorted_out.extend(existing);
+    }
+
+    // into_sorted_vec_fast as a boxed slice. The Vec is allocated at the exact length up front, so
+    // converting it doesn't reallocate.
+    pub fn into_sorted_boxed_slice(self) -> Box<[T]> {
+        self.into_sorted_vec_fast().into_boxed_slice()
+    }
+
+    // merge_sorted_into for a borrowed slice, returning the combined sorted Vec. `sorted` has to
+    // be in the heap's extraction order.
+    pub fn merge_with_sorted(self, sorted: &[T]) -> Vec<T> {
+        let mut merged = sorted.to_vec();
+        self.merge_sorted_into(&mut merged);
+        merged
+    }
+
+    // Same output as into_sorted_vec. Since the heap is being torn down anyway, the index table
+    // is dropped up front and only `elements` is maintained while popping.
+    pub fn into_sorted_vec_fast(mut self) -> Vec<T> {
+        self.element_indices.clear();
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(last_entry) = self.pop_back() {
+            if self.is_empty() {
+                sorted.push(last_entry.0);
+            } else {
+                sorted.push(std::mem::replace(&mut self.elements[0], last_entry.0));
+                self.insertion_order[0] = last_entry.1;
+                self.sift_down_untracked(0);
+            }
+        }
+        sorted
+    }
+
+    pub fn remove_object(&mut self, object: &T) -> Option<T> {
+        // Takes the occurrence closest to the tail: the last element moves into the freed slot, and
+        // the nearer the two are the less there is to sift (nothing at all for the last element).
+        let removed = self.remove_object_last(object);
+        self.compact_table_if_sparse();
+        self.debug_assert_consistent();
+        removed
+    }
+
+    // Removes every copy of `object` with a single rebuild, returning them in storage order. Empty if
+    // `object` isn't in the heap.
+    pub fn remove_object_all(&mut self, object: &T) -> Vec<T> {
+        let copies: std::collections::HashSet<usize> = self
+            .occurrences(object)
+            .filter(|(_, stored)| *stored == object)
+            .map(|(index, _)| index)
+            .collect();
+        if copies.is_empty() {
+            return vec![];
+        }
+        let root = self.root_insertion();
+        let removed = self
+            .extract_matching(|index, _| copies.contains(&index))
+            .into_iter()
+            .map(|(element, _)| element)
+            .collect();
+        self.notify_root_change(root);
+        removed
+    }
+
+    // Removes the occurrence stored at the highest index, typically the deepest and most recently
+    // inserted copy, instead of the first tracked one.
+    pub fn remove_object_last(&mut self, object: &T) -> Option<T> {
+        let index_to_remove = *self.get_index(object)?.iter().max()?;
+        let root = self.root_insertion();
+        let removed = self.remove_at(index_to_remove);
+        self.notify_root_change(root);
+        removed
+    }
+
+    // Removes the first element (in storage order) for which `pred` holds.
+    // The predicate can't be looked up in the index table, so finding the element is O(n).
+    pub fn remove_first_matching(&mut self, pred: impl Fn(&T) -> bool) -> Option<T> {
+        let index_to_remove = self.elements.iter().position(pred)?;
+        let root = self.root_insertion();
+        let removed = self.remove_at(index_to_remove);
+        self.notify_root_change(root);
+        removed
+    }
+
+    // Applies `f` in place to the first occurrence of `target` and re-sifts just that element.
+    // Returns false if `target` isn't in the heap.
+    pub fn update<F: FnOnce(&mut T)>(&mut self, target: &T, f: F) -> bool {
+        let index = match self.get_index(target) {
+            Some(present_indices) => present_indices[0],
+            None => return false,
+        };
+        let root = self.root_insertion();
+        let updated = self.insertion_order[index];
+        self.remove_from_table(index, index);
+        self.bump_version();
+        f(&mut self.elements[index]);
+        self.rehash(index);
+        self.update_table_for_element_entry(index);
+        let res = self.check_heap_invariants_at(index);
+        self.ensure_heap_invariants(res, index);
+        self.notify_root_update(root, updated);
+        true
+    }
+
+    // Batch version of update: replaces one stored copy of every `old` by its `new`, then rebuilds
+    // once in O(n) instead of re-sifting after each replacement. An `old` that isn't present is
+    // skipped. Olds are matched against the elements as they were before the call, so an update
+    // can't target the `new` of an earlier one.
+    pub fn change_priorities(&mut self, updates: impl IntoIterator<Item = (T, T)>) {
+        let mut replaced = std::collections::HashSet::new();
+        let mut originals = vec![];
+        for (old, new) in updates {
+            let index = self
+                .get_index(&old)
+                .into_iter()
+                .flatten()
+                .copied()
+                .find(|index| !replaced.contains(index) && self.elements[*index] == old);
+            if let Some(index) = index {
+                originals.push(std::mem::replace(&mut self.elements[index], new));
+                replaced.insert(index);
+            }
+        }
+        if !originals.is_empty() {
+            self.rebuild();
+        }
+    }
+
+    // Swaps the first occurrences of `a` and `b`, then re-sifts both so the heap property holds
+    // again. Returns false, leaving the heap alone, if either is absent.
+    pub fn swap_values(&mut self, a: &T, b: &T) -> bool {
+        let (ind_a, ind_b) = match (self.get_index(a), self.get_index(b)) {
+            (Some(indices_a), Some(indices_b)) => (indices_a[0], indices_b[0]),
+            _ => return false,
+        };
+        if ind_a == ind_b {
+            return true;
+        }
+        self.swap_elements(ind_a, ind_b);
+        // The deeper element is fixed first. If one position is an ancestor of the other, that one
+        // now holds the higher priority value, which bubbles up past the other without leaving
+        // anything else out of place. Sifting can move the other element though, so it is found
+        // again by its insertion number.
+        let (deeper, shallower) = (ind_a.max(ind_b), ind_a.min(ind_b));
+        let (other, other_value) = (
+            self.insertion_order[shallower],
+            self.elements[shallower].clone(),
+        );
+        let res = self.check_heap_invariants_at(deeper);
+        self.ensure_heap_invariants(res, deeper);
+        let other_index = self
+            .get_index(&other_value)
+            .into_iter()
+            .flatten()
+            .copied()
+            .find(|ind| self.insertion_order[*ind] == other)
+            .unwrap();
+        let res = self.check_heap_invariants_at(other_index);
+        self.ensure_heap_invariants(res, other_index);
+        self.debug_assert_consistent();
+        true
+    }
+
+    // The relaxation step of shortest path style algorithms, for elements whose Eq/Hash identify
+    // the item and whose ordering is its priority. Inserts `item` if absent, replaces the stored
+    // copy if `item` has at least its priority, and otherwise (or when a hard capacity leaves no
+    // room to insert it) leaves the heap alone and returns false.
+    pub fn relax(&mut self, item: T) -> bool {
+        let index = match self.get_index(&item) {
+            Some(present_indices) => present_indices[0],
+            None => return self.insert(item).is_ok(),
+        };
+        let existing = self.element_at(index).unwrap();
+        if self.verify_priority(existing, &item) && !self.verify_priority(&item, existing) {
+            return false;
+        }
+        let target = item.clone();
+        self.update(&target, |existing| *existing = item)
+    }
+
+    fn remove_at(&mut self, index_to_remove: usize) -> Option<T> {
+        let last_element_index = self.len().checked_sub(1)?;
+        if index_to_remove > last_element_index {
+            return None;
+        }
+        // If the element to be removed is the first element in the vector, then we simply call extract_root().
+        // On the otherhand, if the element is the last element in the vector, we remove the element's index entry from the table
+        // and then call pop_back on the vector.
+        match index_to_remove {
+            0 => self.extract_root(),
+            x if x == last_element_index => self.pop_back_tracked(),
+            _ => {
+                self.swap_elements(index_to_remove, last_element_index);
+                let removed_element = self.pop_back_tracked();
+                let res = self.check_heap_invariants_at(index_to_remove);
+                self.ensure_heap_invariants(res, index_to_remove);
+                removed_element
             }
-        } else {
-            None
         }
     }
 
@@ -125,11 +1311,10 @@ where
     fn check_heap_invariants_at(
         &self,
         disturbed_index: usize,
-        element: &T,
     ) -> (Option<&'static str>, Option<&'static str>) {
         match (
-            self.verify_parent(disturbed_index, element),
-            self.verify_children(disturbed_index, element),
+            self.verify_parent(disturbed_index),
+            self.verify_children(disturbed_index),
         ) {
             (true, true) => (None, None),
             (true, false) => (None, Some(CHILDREN_VIOLATION)),
@@ -138,22 +1323,20 @@ where
         }
     }
 
-    fn handle_table_changes(&mut self) {
-        if self.elements.front().is_some() {
-            self.remove_from_table(0, 0);
-        }
-        if self.elements.back().is_some() {
-            let vec_len = self.elements.len() - 1;
-            self.remove_from_table(vec_len, vec_len);
-        }
+    // Recomputes the cached hash at `element_index` after its value was changed in place.
+    fn rehash(&mut self, element_index: usize) {
+        self.hashes[element_index] =
+            Self::hash_value(&self.hash_builder, &self.elements[element_index]);
     }
 
     fn update_table_for_element_entry(&mut self, element_index: usize) {
-        let hash_value =
-            Self::hash_value(&self.hash_builder, &self.element_at(element_index).unwrap());
+        let hash_value = self.hashes[element_index];
 
         if let Some(element_present_at) = self.element_indices.get_mut(&hash_value) {
             //  Duplicates
+            if element_present_at.is_empty() {
+                self.empty_buckets -= 1;
+            }
             element_present_at.push(element_index);
         } else {
             // Insert the elements index in the vector [Element is unique in the vector]
@@ -168,7 +1351,11 @@ where
         self.update_table_for_element_entry(ind2);
     }
 
+    // Every lookup goes through here (or find_index_by), so an empty heap never hashes at all.
     pub(crate) fn get_index(&self, element: &T) -> Option<&[usize]> {
+        if self.is_empty() {
+            return None;
+        }
         let hash_value = Self::hash_value(&self.hash_builder, element);
         let present_indices = self.element_indices.get(&hash_value).and_then(|indicies| {
             if indicies.is_empty() {
@@ -180,27 +1367,387 @@ where
         present_indices.map(|indices| &indices[..])
     }
 
+    // Index of a stored element matching `is_match`, searched for in the table bucket `key`
+    // hashes to. Lets wrappers whose elements hash exactly like one of their fields look them up
+    // by that field alone.
+    pub(crate) fn find_index_by<Q: std::hash::Hash>(
+        &self,
+        key: &Q,
+        is_match: impl Fn(&T) -> bool,
+    ) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        let hash_value = self.hash_builder.hash_one(key);
+        self.element_indices
+            .get(&hash_value)?
+            .iter()
+            .copied()
+            .find(|ind| is_match(&self.elements[*ind]))
+    }
+
+    // Any stored element whose priority equals that of `value` under the heap's ordering, which
+    // doesn't have to mean it is Eq-equal to `value`. Subtrees below a lower priority element can't
+    // hold a match and get skipped.
+    pub fn find_priority(&self, value: &T) -> Option<&T> {
+        let mut pending = vec![0];
+        while let Some(ind) = pending.pop() {
+            let element = match self.element_at(ind) {
+                Some(element) => element,
+                None => continue,
+            };
+            match self.priority_ordering(element, value) {
+                std::cmp::Ordering::Equal => return Some(element),
+                std::cmp::Ordering::Less => {
+                    pending.extend(self.children_indices(ind).iter().flatten())
+                }
+                std::cmp::Ordering::Greater => {}
+            }
+        }
+        None
+    }
+
+    // An element other than the root sharing the root's priority. Such an element has to have an
+    // equal priority parent all the way up, so only the root's children need checking.
+    pub fn any_equal_to_root(&self) -> Option<&T> {
+        let root = self.peek()?;
+        self.children_indices(0)
+            .iter()
+            .flatten()
+            .map(|ind| self.element_at(*ind).unwrap())
+            .find(|child| self.priority_ordering(child, root) == std::cmp::Ordering::Equal)
+    }
+
+    // Clones of the k highest priority elements in extraction order, leaving the heap untouched.
+    // Only the root's frontier is explored, so this is O(k log k) rather than a full sort.
+    pub fn snapshot_top(&self, k: usize) -> Vec<T> {
+        self.top_indices(k)
+            .into_iter()
+            .map(|ind| self.element_at(ind).unwrap().clone())
+            .collect()
+    }
+
+    // snapshot_top into a caller owned buffer: fills `out` with up to `out.len()` of the highest
+    // priority elements in extraction order and returns how many were written. Nothing at all is
+    // allocated: each slot gets its own scan for the best element after the previous slot's, so
+    // this takes O(n * k) comparisons and suits small buffers. Equal priorities are written in
+    // insertion order.
+    pub fn top_k_into(&self, out: &mut [T]) -> usize {
+        let mut previous: Option<usize> = None;
+        let mut written = 0;
+        while written < out.len() {
+            let next = (0..self.len())
+                .filter(|ind| {
+                    previous.is_none_or(|prev| {
+                        self.extraction_order(prev, *ind) == std::cmp::Ordering::Less
+                    })
+                })
+                .min_by(|a, b| self.extraction_order(*a, *b));
+            let Some(ind) = next else { break };
+            out[written].clone_from(&self.elements[ind]);
+            previous = Some(ind);
+            written += 1;
+        }
+        written
+    }
+
+    // Total order of two stored elements, by priority and then by insertion.
+    fn extraction_order(&self, ind1: usize, ind2: usize) -> std::cmp::Ordering {
+        self.priority_ordering(&self.elements[ind1], &self.elements[ind2])
+            .then(self.insertion_order[ind1].cmp(&self.insertion_order[ind2]))
+    }
+
+    // The element that would be extracted k-th (0 being the root), found the same way as
+    // snapshot_top in O(k log k). None if k >= len.
+    pub fn kth(&self, k: usize) -> Option<&T> {
+        if k >= self.len() {
+            return None;
+        }
+        self.top_indices(k + 1)
+            .last()
+            .and_then(|ind| self.element_at(*ind))
+    }
+
+    // Indices of the k highest priority elements in extraction order. The next best element is
+    // always a child of one already picked, so candidates are kept in a small auxiliary heap.
+    fn top_indices(&self, k: usize) -> Vec<usize> {
+        let mut candidates = std::collections::BinaryHeap::new();
+        let mut top = Vec::with_capacity(k.min(self.len()));
+        if !self.is_empty() {
+            candidates.push(self.candidate(0));
+        }
+        while top.len() < k {
+            let best = match candidates.pop() {
+                Some(best) => best.index,
+                None => break,
+            };
+            top.push(best);
+            self.children_indices(best)
+                .iter()
+                .flatten()
+                .for_each(|child| candidates.push(self.candidate(*child)));
+        }
+        top
+    }
+
+    fn candidate(&self, index: usize) -> Candidate<'_, T> {
+        Candidate {
+            element: self.element_at(index).unwrap(),
+            insertion: self.insertion_order[index],
+            index,
+            kind: self.kind,
+            stable: self.stable,
+            total_cmp: self.total_cmp,
+        }
+    }
+
+    // Every position `item` is stored at, along with the stored value. Empty if it's absent.
+    pub fn occurrences(&self, item: &T) -> impl Iterator<Item = (usize, &T)> + '_ {
+        self.get_index(item)
+            .unwrap_or(&[])
+            .iter()
+            .map(move |index| (*index, self.element_at(*index).unwrap()))
+    }
+
+    // Same positions as occurrences, but owned, so the heap can be mutated while holding them.
+    pub fn indices_of(&self, item: &T) -> Vec<usize> {
+        self.get_index(item)
+            .map_or_else(Vec::new, <[usize]>::to_vec)
+    }
+
+    // Position `item` would come out at, 0 meaning it would be extracted first. Equal priority
+    // elements share a rank. The heap only orders along paths, so this counts the higher priority
+    // elements in O(n).
+    pub fn rank_of(&self, item: &T) -> Option<usize> {
+        self.occurrences(item).next()?;
+        Some(
+            self.elements
+                .iter()
+                .filter(|other| self.priority_ordering(other, item) == std::cmp::Ordering::Less)
+                .count(),
+        )
+    }
+
+    // Multiplicity aware: every item in `items` has to be matched by its own copy in the heap.
+    pub fn contains_all(&self, items: impl IntoIterator<Item = T>) -> bool {
+        let mut unmatched_copies: HashMap<u64, usize> = HashMap::new();
+        items.into_iter().all(|item| {
+            let hash_value = Self::hash_value(&self.hash_builder, &item);
+            let copies = unmatched_copies.entry(hash_value).or_insert_with(|| {
+                self.element_indices
+                    .get(&hash_value)
+                    .map_or(0, |indices| indices.len())
+            });
+            match copies {
+                0 => false,
+                _ => {
+                    *copies -= 1;
+                    true
+                }
+            }
+        })
+    }
+
     fn remove_from_table(&mut self, element_ind: usize, element_was_at: usize) {
-        let hash_value =
-            Self::hash_value(&self.hash_builder, &self.element_at(element_ind).unwrap());
+        let hash_value = self.hashes[element_ind];
         if let Some(indices) = self.element_indices.get_mut(&hash_value) {
             let items_to_be_retained: Vec<usize> = indices
                 .iter()
                 .filter(|ind| **ind != element_was_at)
                 .copied()
                 .collect();
+            let was_empty = indices.is_empty();
             indices.clear();
             assert_eq!(indices.len(), 0);
             items_to_be_retained.into_iter().for_each(|ind| {
                 indices.push(ind);
             });
+            if indices.is_empty() && !was_empty {
+                self.empty_buckets += 1;
+            }
+        }
+    }
+
+    // Drops the table entries that no longer track any index once they make up more than
+    // `compaction_ratio` of the table, and releases the memory they held.
+    fn compact_table_if_sparse(&mut self) {
+        let buckets = self.element_indices.len();
+        if buckets < COMPACTION_MIN_BUCKETS
+            || (self.empty_buckets as f64) <= buckets as f64 * self.compaction_ratio
+        {
+            return;
         }
+        self.element_indices
+            .retain(|_, indices| !indices.is_empty());
+        self.element_indices.shrink_to_fit();
+        self.empty_buckets = 0;
     }
 
     pub fn peek(&self) -> Option<&T> {
         self.elements.front()
     }
 
+    // An owned copy of the root, for when the borrow from peek can't be held (e.g. behind a lock).
+    pub fn peek_clone(&self) -> Option<T> {
+        self.peek().cloned()
+    }
+
+    // Looks at the heap as if it were of the opposite kind, without rebuilding anything.
+    pub fn reversed(&self) -> ReversedView<'_, T, S> {
+        ReversedView { heap: self }
+    }
+
+    // Walks the implicit tree, starting at the root.
+    pub fn cursor(&self) -> Cursor<'_, T, S> {
+        Cursor {
+            heap: self,
+            index: 0,
+        }
+    }
+
+    // Where the root is stored. That is index 0 today, callers shouldn't rely on it though.
+    pub fn root_index(&self) -> Option<usize> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
+    pub fn peek_with_len(&self) -> Option<(&T, usize)> {
+        self.peek().map(|root| (root, self.len()))
+    }
+
+    // Mutable access to the root. The guard keeps a copy of the original value and only
+    // re-hashes and sifts on drop if the root was actually changed.
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T, S>> {
+        let original = self.peek()?.clone();
+        Some(PeekMut {
+            heap: self,
+            original: Some(original),
+        })
+    }
+
+    // Returns (highest_priority, lowest_priority). The root is O(1), the lowest priority element
+    // has to be one of the leaves so only the leaf range gets scanned.
+    pub fn extremes(&self) -> Option<(&T, &T)> {
+        let root = self.peek()?;
+        let worst = self.element_at(self.worst_leaf_index()?).unwrap();
+        Some((root, worst))
+    }
+
+    // Leaves occupy the second half of `elements`, the lowest priority element is always among them.
+    fn worst_leaf_index(&self) -> Option<usize> {
+        let mut leaves = self.len() / 2..self.len();
+        let first_leaf = leaves.next()?;
+        Some(leaves.fold(first_leaf, |worst, ind| {
+            if self.has_priority(ind, worst) {
+                worst
+            } else {
+                ind
+            }
+        }))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.elements.capacity()
+    }
+
+    // An estimate going by allocated capacities: memory owned by the elements themselves (e.g.
+    // behind a String) isn't counted, and the table's control bytes and allocator overhead are
+    // ignored.
+    pub fn memory_footprint(&self) -> MemoryReport {
+        let bucket = std::mem::size_of::<u64>() + std::mem::size_of::<Vec<usize>>();
+        let index_lists: usize = self
+            .element_indices
+            .values()
+            .map(|indices| indices.capacity() * std::mem::size_of::<usize>())
+            .sum();
+        MemoryReport {
+            elements: self.elements.capacity() * std::mem::size_of::<T>(),
+            insertion_order: self.insertion_order.capacity() * std::mem::size_of::<u64>(),
+            hashes: self.hashes.capacity() * std::mem::size_of::<u64>(),
+            index_table: self.element_indices.capacity() * bucket + index_lists,
+        }
+    }
+
+    pub fn set_growth_policy(&mut self, growth_policy: GrowthPolicy) {
+        self.growth_policy = growth_policy;
+    }
+
+    // Calls `callback` with the new root whenever an insert, extraction or removal leaves a
+    // different element at the root, or the root's value is changed in place (update, peek_mut,
+    // a coalescing insert), e.g. so a scheduler can re-arm its timer for a sooner deadline. Not
+    // called when the heap becomes empty, nor by the bulk operations that rebuild the whole heap
+    // (other than extend).
+    // Like DuplicatePolicy::coalesce the callback is stored without naming T, see Merge, which is
+    // why elements have to be 'static here.
+    pub fn set_on_root_change(&mut self, callback: impl Fn(&T) + Send + Sync + 'static)
+    where
+        T: 'static,
+    {
+        self.on_root_change = Some(RootCallback {
+            callback: Arc::new(move |root: &dyn Any| callback(root.downcast_ref::<T>().unwrap())),
+            as_any: |root| root,
+        });
+    }
+
+    // Insertion numbers are never reused, so they tell whether the root is still the same element.
+    fn root_insertion(&self) -> Option<u64> {
+        self.insertion_order.front().copied()
+    }
+
+    fn notify_root_change(&self, previous_root: Option<u64>) {
+        if self.root_insertion() != previous_root {
+            self.call_root_callback();
+        }
+    }
+
+    // For a value changed in place: the root is also new if it now is the `updated` element,
+    // whether the change moved it up or it already was the root.
+    fn notify_root_update(&self, previous_root: Option<u64>, updated: u64) {
+        if self.root_insertion() == Some(updated) {
+            self.call_root_callback();
+        } else {
+            self.notify_root_change(previous_root);
+        }
+    }
+
+    fn call_root_callback(&self) {
+        if let (Some(on_root_change), Some(root)) = (&self.on_root_change, self.peek()) {
+            (on_root_change.callback)((on_root_change.as_any)(root));
+        }
+    }
+
+    // remove_object compacts the index table once more than `ratio` of its entries are left
+    // without any element. Defaults to 0.75.
+    pub fn set_compaction_threshold(&mut self, ratio: f64) {
+        self.compaction_ratio = ratio;
+    }
+
+    // Extend appends everything and rebuilds in O(n) once it is handed at least
+    // `len * ratio` elements (going by the iterator's size hint), and inserts them one by one
+    // otherwise. Defaults to 1.0, i.e. rebuild when the heap would at least double.
+    pub fn set_extend_rebuild_threshold(&mut self, ratio: f64) {
+        self.extend_rebuild_ratio = ratio;
+    }
+
+    // Once an extract leaves fewer than `capacity * threshold_ratio` elements, the storage is
+    // shrunk to twice the remaining length so a heap that spiked doesn't hold on to its peak memory.
+    pub fn auto_shrink(&mut self, threshold_ratio: f64) {
+        self.shrink_ratio = Some(threshold_ratio);
+    }
+
+    fn shrink_if_sparse(&mut self) {
+        if let Some(ratio) = self.shrink_ratio {
+            if (self.len() as f64) < self.elements.capacity() as f64 * ratio {
+                self.elements.shrink_to(self.len() * 2);
+                self.insertion_order.shrink_to(self.len() * 2);
+                self.hashes.shrink_to(self.len() * 2);
+            }
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.elements.len()
     }
@@ -209,25 +1756,311 @@ where
         self.elements.is_empty()
     }
 
+    // Same as len, paired with count_distinct for multiset heaps.
+    pub fn count_total(&self) -> usize {
+        self.len()
+    }
+
+    // Number of distinct values, i.e. table entries that still track at least one index.
+    // Entries emptied by removals are kept around by the table and must not be counted.
+    pub fn count_distinct(&self) -> usize {
+        self.element_indices
+            .values()
+            .filter(|indices| !indices.is_empty())
+            .count()
+    }
+
+    pub fn is_min_heap(&self) -> bool {
+        matches!(self.kind, HeapKind::Min)
+    }
+
+    pub fn is_max_heap(&self) -> bool {
+        matches!(self.kind, HeapKind::Max)
+    }
+
+    // First index in level order where the heap property doesn't hold between an element and its
+    // parent or children. A broken parent/child pair is reported at the parent, being visited first.
+    pub fn first_violation(&self) -> Option<usize> {
+        (0..self.len()).find(|ind| !self.verify_heap_property(*ind))
+    }
+
+    // Changes whenever the heap is mutated, so code caching indices can tell they may be stale.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn checked_index(&self, index: usize) -> Option<CheckedIndex> {
+        if self.is_valid_index(index) {
+            Some(CheckedIndex {
+                index,
+                version: self.version,
+            })
+        } else {
+            None
+        }
+    }
+
+    // None if the heap was mutated after `checked` was taken.
+    pub fn resolve(&self, checked: CheckedIndex) -> Option<&T> {
+        if checked.version == self.version {
+            self.element_at(checked.index)
+        } else {
+            None
+        }
+    }
+
+    pub fn neighbors(&self, index: usize) -> Option<Neighbors<'_, T>> {
+        let element = self.element_at(index)?;
+        let [left, right] = self.children_indices(index);
+        Some(Neighbors {
+            element,
+            parent: self
+                .parent_index(index)
+                .and_then(|parent_ind| self.element_at(parent_ind)),
+            children: [
+                left.and_then(|ind| self.element_at(ind)),
+                right.and_then(|ind| self.element_at(ind)),
+            ],
+        })
+    }
+
+    // Every element level by level, one per line with its index, parent and children, e.g.
+    // `1: 3 parent: 1 children: [4, 8]`. Only depends on the layout, never on the hasher, so it
+    // is stable enough for snapshot tests.
+    pub fn debug_dump(&self) -> String {
+        use std::fmt::Write;
+        let mut dump = String::new();
+        let (mut level, mut level_start) = (0, 0);
+        while level_start < self.len() {
+            let level_end = (level_start * 2 + 1).min(self.len());
+            writeln!(dump, "level {}", level).unwrap();
+            for index in level_start..level_end {
+                let neighbors = self.neighbors(index).unwrap();
+                let parent = neighbors
+                    .parent
+                    .map_or_else(|| "-".to_string(), |parent| format!("{:?}", parent));
+                let children: Vec<&T> = neighbors.children.iter().flatten().copied().collect();
+                writeln!(
+                    dump,
+                    "  {}: {:?} parent: {} children: {:?}",
+                    index, neighbors.element, parent, children
+                )
+                .unwrap();
+            }
+            level += 1;
+            level_start = level_end;
+        }
+        dump
+    }
+
+    // Relabels the index table after the storage was rearranged through raw_elements_mut. The
+    // caller must already have performed every swap in `indices`, in that order; the table is only
+    // told about them, nothing gets moved here. Panics on an out of range index.
+    pub fn notify_swapped(&mut self, indices: &[(usize, usize)]) {
+        self.bump_version();
+        // By now `elements` is in its final state, so replay the swaps on positions to find
+        // where each element that moved came from.
+        let mut origin: HashMap<usize, usize> = HashMap::new();
+        for (ind1, ind2) in indices.iter().copied() {
+            assert!(self.is_valid_index(ind1) && self.is_valid_index(ind2));
+            let from1 = *origin.get(&ind1).unwrap_or(&ind1);
+            let from2 = *origin.get(&ind2).unwrap_or(&ind2);
+            origin.insert(ind1, from2);
+            origin.insert(ind2, from1);
+        }
+        origin.retain(|current, original| current != original);
+        // The cached hashes and insertion numbers haven't moved along with the elements yet.
+        let moved: Vec<(usize, u64, u64)> = origin
+            .iter()
+            .map(|(current, original)| {
+                (
+                    *current,
+                    self.hashes[*original],
+                    self.insertion_order[*original],
+                )
+            })
+            .collect();
+        moved
+            .into_iter()
+            .for_each(|(current, hash_value, insertion)| {
+                self.hashes[current] = hash_value;
+                self.insertion_order[current] = insertion;
+            });
+        origin
+            .iter()
+            .for_each(|(current, original)| self.remove_from_table(*current, *original));
+        origin
+            .keys()
+            .for_each(|current| self.update_table_for_element_entry(*current));
+    }
+
+    // Repairs the heap after the elements at `dirty_indices` were changed in place from outside the
+    // heap's own operations, without a full rebuild. Every index must be in range and everything
+    // else in the heap (values, positions and table) must still be as the heap left it.
+    // Panics on an out of range index.
+    pub fn fix_at(&mut self, dirty_indices: &[usize]) {
+        let mut dirty = dirty_indices.to_vec();
+        dirty.sort_unstable();
+        dirty.dedup();
+        for ind in dirty.iter().copied() {
+            assert!(self.is_valid_index(ind));
+            // The cached hash is still the old value's, which is what the table entry is keyed by.
+            self.remove_from_table(ind, ind);
+            self.rehash(ind);
+            self.update_table_for_element_entry(ind);
+        }
+        self.bump_version();
+        // Only subtrees headed by a dirty element or one of its ancestors can be broken. Sifting
+        // those heads down bottom up is rebuild restricted to them, and just as correct. Re-sifting
+        // each dirty element on its own isn't: a sift can run into another, still misplaced one.
+        let mut heads = std::collections::BTreeSet::new();
+        for ind in dirty {
+            let mut head = Some(ind);
+            while let Some(ind) = head {
+                if !heads.insert(ind) {
+                    break;
+                }
+                head = self.parent_index(ind);
+            }
+        }
+        heads
+            .into_iter()
+            .rev()
+            .for_each(|head| self.bubble_down(head));
+        self.debug_assert_consistent();
+    }
+
+    pub fn is_valid_index(&self, index: usize) -> bool {
+        index < self.len()
+    }
+
+    // Positions move around on every mutation, so an index cached by the caller can go stale.
+    // Returns true only if `expected` is still stored at `index`.
+    pub fn verify_index_holds(&self, index: usize, expected: &T) -> bool {
+        self.element_at(index) == Some(expected)
+    }
+
+    // Compiles down to nothing in release builds.
+    fn debug_assert_consistent(&self) {
+        if !cfg!(debug_assertions) || self.len() > DEBUG_CHECK_LIMIT {
+            return;
+        }
+        debug_assert_eq!(self.first_violation(), None, "heap property broken");
+        debug_assert!(self.table_in_sync(), "index table out of sync");
+    }
+
+    // Rebuilds the index table from `elements` and reports how many (value, index) entries the old
+    // table got wrong: entries missing from it plus entries it shouldn't have had. 0 means the
+    // table was already correct.
+    pub fn repair_index(&mut self) -> usize {
+        let tracked: std::collections::HashSet<(u64, usize)> = self
+            .element_indices
+            .iter()
+            .flat_map(|(hash_value, indices)| indices.iter().map(move |ind| (*hash_value, *ind)))
+            .collect();
+        // A bucket listing the same index twice counts as one bad entry per extra copy.
+        let repeated = self.element_indices.values().map(Vec::len).sum::<usize>() - tracked.len();
+        self.rebuild_table();
+        let correct: std::collections::HashSet<(u64, usize)> = self
+            .element_indices
+            .iter()
+            .flat_map(|(hash_value, indices)| indices.iter().map(move |ind| (*hash_value, *ind)))
+            .collect();
+        tracked.symmetric_difference(&correct).count() + repeated
+    }
+
+    // Whether the heap property holds everywhere and the index table tracks exactly the stored
+    // elements. O(n).
+    pub fn validate(&self) -> bool {
+        self.first_violation().is_none() && self.table_in_sync()
+    }
+
+    // Rebuilds the heap and the index table from scratch if validate fails, returning whether
+    // anything had to be repaired. The check is O(n), as is the repair.
+    pub fn ensure_valid(&mut self) -> bool {
+        if self.validate() {
+            return false;
+        }
+        self.rebuild();
+        true
+    }
+
+    fn table_in_sync(&self) -> bool {
+        self.element_indices.values().map(Vec::len).sum::<usize>() == self.len()
+            && self.hashes.len() == self.len()
+            && self.elements.iter().enumerate().all(|(ind, element)| {
+                self.hashes[ind] == Self::hash_value(&self.hash_builder, element)
+                    && self
+                        .get_index(element)
+                        .is_some_and(|indices| indices.contains(&ind))
+            })
+    }
+
+    // Whether `obj1` has priority over (or the same priority as) `obj2` in this heap, going
+    // through the same comparison the heap itself uses for its kind (and Ord, see new_ord).
+    pub fn compare(&self, obj1: &T, obj2: &T) -> bool {
+        self.verify_priority(obj1, obj2)
+    }
+
+    // Where `item` would come out relative to the current root: Less means before it, so it would
+    // become the new root. None if the heap is empty.
+    pub fn cmp_to_root(&self, item: &T) -> Option<std::cmp::Ordering> {
+        self.peek().map(|root| self.priority_ordering(item, root))
+    }
+
     fn verify_priority(&self, obj1: &T, obj2: &T) -> bool {
-        match self.kind {
-            HeapKind::Max => obj1 >= obj2,
-            HeapKind::Min => obj1 <= obj2,
+        self.kind.prioritizes_with(self.total_cmp, obj1, obj2)
+    }
+
+    fn element_cmp(&self, obj1: &T, obj2: &T) -> Option<std::cmp::Ordering> {
+        match self.total_cmp {
+            Some(cmp) => Some(cmp(obj1, obj2)),
+            None => obj1.partial_cmp(obj2),
+        }
+    }
+
+    // Less means obj1 would be extracted before obj2.
+    fn priority_ordering(&self, obj1: &T, obj2: &T) -> std::cmp::Ordering {
+        match (
+            self.verify_priority(obj1, obj2),
+            self.verify_priority(obj2, obj1),
+        ) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            _ => std::cmp::Ordering::Equal,
+        }
+    }
+
+    // verify_priority for two stored elements. Stable heaps order equal priority elements by
+    // insertion, so the one inserted first comes out first.
+    fn has_priority(&self, ind1: usize, ind2: usize) -> bool {
+        let obj1 = self.element_at(ind1).unwrap();
+        let obj2 = self.element_at(ind2).unwrap();
+        if self.stable && self.element_cmp(obj1, obj2) == Some(std::cmp::Ordering::Equal) {
+            self.insertion_order[ind1] <= self.insertion_order[ind2]
+        } else {
+            self.verify_priority(obj1, obj2)
         }
     }
 
+    // Only the parent is compared, so this stops at the root even when the element there can't be
+    // ordered against its children.
     fn bubble_up(&mut self, start_ind: usize) {
         let mut new_element_pos = start_ind;
-        while !self.verify_heap_property(new_element_pos) {
-            let parent_ind = self.parent_index(new_element_pos).unwrap();
+        while let Some(parent_ind) = self.parent_index(new_element_pos) {
+            if self.has_priority(parent_ind, new_element_pos) {
+                break;
+            }
             self.swap_elements(new_element_pos, parent_ind);
             new_element_pos = parent_ind;
         }
     }
 
+    // Only the children are compared, so this can run while the element above is still out of
+    // place, and it stops at a leaf even when the element can't be ordered against its parent.
     fn bubble_down(&mut self, start_ind: usize) {
         let mut new_element_pos = start_ind;
-        while !self.verify_heap_property(new_element_pos) {
+        while !self.verify_children(new_element_pos) {
             let children_indices = self.children_indices(new_element_pos);
             let priority_ind = self.index_with_priority(children_indices);
             self.swap_elements(priority_ind, new_element_pos);
@@ -235,18 +2068,63 @@ where
         }
     }
 
+    // Appends `items` (about `incoming` of them) without sifting, then rebuilds everything in O(n).
+    fn append_and_rebuild(&mut self, items: impl IntoIterator<Item = T>, incoming: usize) {
+        self.elements.reserve(incoming);
+        self.insertion_order.reserve(incoming);
+        for item in items {
+            self.elements.push_back(item);
+            self.insertion_order.push_back(self.next_insertion);
+            self.next_insertion += 1;
+        }
+        self.rebuild();
+    }
+
+    // Restores the heap property over all of `elements` bottom up in O(n) and recomputes the index table.
+    fn rebuild(&mut self) {
+        // `elements` may have been replaced wholesale, the hashes are recomputed by rebuild_table
+        // and only need the right length while sifting.
+        self.hashes.resize(self.len(), 0);
+        for ind in (0..self.len() / 2).rev() {
+            self.sift_down_untracked(ind);
+        }
+        self.rebuild_table();
+    }
+
+    fn rebuild_table(&mut self) {
+        self.bump_version();
+        let hash_builder = &self.hash_builder;
+        self.hashes = self
+            .elements
+            .iter()
+            .map(|element| Self::hash_value(hash_builder, element))
+            .collect();
+        self.element_indices.clear();
+        self.empty_buckets = 0;
+        (0..self.len()).for_each(|ind| self.update_table_for_element_entry(ind));
+    }
+
+    // Same as bubble_down but only moves `elements` around, leaving the index table untouched.
+    // Callers are expected to discard or rebuild the table afterwards.
+    fn sift_down_untracked(&mut self, start_ind: usize) {
+        let mut new_element_pos = start_ind;
+        while !self.verify_children(new_element_pos) {
+            let children_indices = self.children_indices(new_element_pos);
+            let priority_ind = self.index_with_priority(children_indices);
+            self.swap_untracked(priority_ind, new_element_pos);
+            new_element_pos = priority_ind;
+        }
+    }
+
     // Verifies the heap property b/w the given node and its parent and children.
     fn verify_heap_property(&self, index: usize) -> bool {
-        let current_node = self.element_at(index).unwrap();
-        self.verify_parent(index, current_node) && self.verify_children(index, current_node)
+        self.verify_parent(index) && self.verify_children(index)
     }
 
     fn index_with_priority(&self, indices: [Option<usize>; 2]) -> usize {
         match (indices[0], indices[1]) {
             (Some(child1_ind), Some(child2_ind)) => {
-                let child1 = self.element_at(child1_ind).unwrap();
-                let child2 = self.element_at(child2_ind).unwrap();
-                if self.verify_priority(child1, child2) {
+                if self.has_priority(child1_ind, child2_ind) {
                     child1_ind
                 } else {
                     child2_ind
@@ -258,23 +2136,19 @@ where
         }
     }
 
-    fn verify_parent(&self, child_node_ind: usize, child: &T) -> bool {
+    fn verify_parent(&self, child_node_ind: usize) -> bool {
         if let Some(parent_ind) = self.parent_index(child_node_ind) {
-            let parent = self.element_at(parent_ind).unwrap();
-            self.verify_priority(parent, child)
+            self.has_priority(parent_ind, child_node_ind)
         } else {
             true
         }
     }
 
-    fn verify_children(&self, index: usize, parent: &T) -> bool {
+    fn verify_children(&self, index: usize) -> bool {
         self.children_indices(index)
             .iter()
             .filter_map(|item| item.as_ref())
-            .all(|child_ind| {
-                let child = self.element_at(*child_ind).unwrap();
-                self.verify_priority(parent, child)
-            })
+            .all(|child_ind| self.has_priority(index, *child_ind))
     }
 
     pub(crate) fn element_at(&self, ind: usize) -> Option<&T> {
@@ -309,38 +2183,439 @@ where
     }
 
     fn hash_value(hash_builder: &S, element: &T) -> u64 {
-        let mut h = hash_builder.build_hasher();
-        element.hash(&mut h);
-        h.finish()
+        hash_builder.hash_one(element)
     }
 
-    fn push_back(&mut self, object: T) {
+    // Returns the index the object was placed at.
+    fn push_back(&mut self, object: T) -> usize {
+        let currently_inserted_index = self.len();
+        if let GrowthPolicy::Linear(step) = self.growth_policy {
+            if self.len() == self.elements.capacity() {
+                self.elements.reserve_exact(step.max(1));
+                self.insertion_order.reserve_exact(step.max(1));
+                self.hashes.reserve_exact(step.max(1));
+            }
+        }
+        self.bump_version();
+        self.hashes
+            .push_back(Self::hash_value(&self.hash_builder, &object));
         self.elements.push_back(object);
-        let currently_inserted_index = self.elements.len() - 1;
+        self.insertion_order.push_back(self.next_insertion);
+        self.next_insertion += 1;
         self.update_table_for_element_entry(currently_inserted_index);
+        currently_inserted_index
     }
 
-    fn push_front(&mut self, object: T) {
-        self.elements.push_front(object);
-        self.update_table_for_element_entry(0);
+    // Removes the last element along with its table entry.
+    fn pop_back_tracked(&mut self) -> Option<T> {
+        let last_element_index = self.len().checked_sub(1)?;
+        self.remove_from_table(last_element_index, last_element_index);
+        self.pop_back().map(|(element, _)| element)
+    }
+
+    fn pop_back(&mut self) -> Option<(T, u64)> {
+        self.bump_version();
+        self.hashes.pop_back();
+        self.elements
+            .pop_back()
+            .zip(self.insertion_order.pop_back())
     }
 
     fn swap_elements(&mut self, ind1: usize, ind2: usize) {
+        let equal_values = self.elements[ind1] == self.elements[ind2];
         //  1, 0
-        self.elements.swap(ind1, ind2);
-        // 3, 4
-        self.update_table_for_swap(ind1, ind2);
+        self.swap_untracked(ind1, ind2);
+        // Equal values share a table entry which already holds both indices, so there is nothing to relabel.
+        if !equal_values {
+            // 3, 4
+            self.update_table_for_swap(ind1, ind2);
+        }
         // 0, 1
     }
+
+    fn swap_untracked(&mut self, ind1: usize, ind2: usize) {
+        self.bump_version();
+        self.elements.swap(ind1, ind2);
+        self.insertion_order.swap(ind1, ind2);
+        self.hashes.swap(ind1, ind2);
+    }
+
+    fn bump_version(&mut self) {
+        self.version = self.version.wrapping_add(1);
+    }
+}
+
+// Yields the elements in priority order, i.e. the order extract_object would return them in.
+pub struct IntoIter<T, S = RandomState> {
+    heap: BinaryHeap<T, S>,
+}
+
+impl<T, S> Iterator for IntoIter<T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.heap.extract_object()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<T, S> IntoIterator for BinaryHeap<T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    type Item = T;
+    type IntoIter = IntoIter<T, S>;
+
+    fn into_iter(self) -> IntoIter<T, S> {
+        IntoIter { heap: self }
+    }
+}
+
+// Adopts a Vec that is already laid out as a heap of the given kind. Nothing is sifted, only the
+// index table gets built, so this is the strict alternative to heapify for trusted layouts.
+impl<T, S> std::convert::TryFrom<(Vec<T>, HeapKind)> for BinaryHeap<T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher + Default,
+{
+    type Error = HeapViolation<T>;
+
+    fn try_from((elements, kind): (Vec<T>, HeapKind)) -> Result<Self, HeapViolation<T>> {
+        let mut heap = Self::new(kind);
+        heap.insertion_order = (0..elements.len() as u64).collect();
+        heap.next_insertion = elements.len() as u64;
+        heap.elements = elements.into();
+        if let Some(index) = heap.first_violation() {
+            return Err(HeapViolation {
+                index,
+                elements: heap.elements.into(),
+            });
+        }
+        heap.rebuild_table();
+        Ok(heap)
+    }
+}
+
+// Panics once a hard capacity is reached, see try_extend for a version that reports it.
+impl<T, S> Extend<T> for BinaryHeap<T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let incoming = iter.size_hint().0;
+        // Bounded heaps and heaps not allowing duplicates have to look at every element as it
+        // comes in, so they always insert.
+        if self.bound.is_some()
+            || self.hard_capacity.is_some()
+            || !matches!(self.duplicate_policy, DuplicatePolicy::Allow)
+            || incoming == 0
+            || (incoming as f64) < self.len() as f64 * self.extend_rebuild_ratio
+        {
+            // Extend has no way to report a full heap, try_extend does.
+            for item in iter {
+                if self.insert(item).is_err() {
+                    panic!(
+                        "hard capacity of {} elements exceeded",
+                        self.hard_capacity.unwrap()
+                    );
+                }
+            }
+            return;
+        }
+        let root = self.root_insertion();
+        self.append_and_rebuild(iter, incoming);
+        self.notify_root_change(root);
+    }
+}
+
+// Read only view of a heap with its ordering flipped, see BinaryHeap::reversed.
+pub struct ReversedView<'a, T, S = RandomState> {
+    heap: &'a BinaryHeap<T, S>,
+}
+
+impl<'a, T, S> ReversedView<'a, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    // The root of the opposite kind heap is the lowest priority element, found with a leaf scan.
+    pub fn peek(&self) -> Option<&'a T> {
+        let heap = self.heap;
+        heap.worst_leaf_index().and_then(|ind| heap.element_at(ind))
+    }
+
+    // Every element, lowest priority first, i.e. the order the opposite kind heap would extract
+    // them in. Needs a sort over references, so O(n log n).
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        let heap = self.heap;
+        let mut ordered: Vec<&T> = heap.elements.iter().collect();
+        ordered.sort_by(|obj1, obj2| heap.priority_ordering(obj2, obj1));
+        ordered.into_iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
+// Read only position in the heap's tree, see BinaryHeap::cursor. Every move returns false and
+// leaves the cursor where it was if there is nothing in that direction.
+pub struct Cursor<'a, T, S = RandomState> {
+    heap: &'a BinaryHeap<T, S>,
+    index: usize,
+}
+
+impl<'a, T, S> Cursor<'a, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    // None only when the heap is empty.
+    pub fn value(&self) -> Option<&'a T> {
+        self.heap.element_at(self.index)
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn left(&mut self) -> bool {
+        self.move_to(self.heap.children_indices(self.index)[0])
+    }
+
+    pub fn right(&mut self) -> bool {
+        self.move_to(self.heap.children_indices(self.index)[1])
+    }
+
+    pub fn up(&mut self) -> bool {
+        self.move_to(self.heap.parent_index(self.index))
+    }
+
+    fn move_to(&mut self, index: Option<usize>) -> bool {
+        match index {
+            Some(index) => {
+                self.index = index;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub struct PeekMut<'a, T, S = RandomState>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    heap: &'a mut BinaryHeap<T, S>,
+    original: Option<T>,
+}
+
+impl<T, S> std::ops::Deref for PeekMut<'_, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.heap.elements[0]
+    }
+}
+
+impl<T, S> std::ops::DerefMut for PeekMut<'_, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.heap.elements[0]
+    }
+}
+
+impl<T, S> Drop for PeekMut<'_, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    fn drop(&mut self) {
+        let original = self.original.take().unwrap();
+        // Eq may only identify the element while its priority lives elsewhere, so an equal value
+        // still needs a sift if its priority changed.
+        let root = &self.heap.elements[0];
+        if *root == original
+            && self.heap.priority_ordering(root, &original) == std::cmp::Ordering::Equal
+        {
+            return;
+        }
+        let root = self.heap.root_insertion();
+        // The cached hash is still the original value's, which is what the table entry is keyed by.
+        self.heap.remove_from_table(0, 0);
+        self.heap.bump_version();
+        self.heap.rehash(0);
+        self.heap.update_table_for_element_entry(0);
+        self.heap.bubble_down(0);
+        // Either the changed root stayed on top or something else moved up, both are a new root.
+        self.heap.notify_root_update(root, root.unwrap());
+    }
+}
+
+pub struct IterMut<'a, T, S = RandomState>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    heap: &'a mut BinaryHeap<T, S>,
+}
+
+impl<'a, 'b, T, S> IntoIterator for &'b mut IterMut<'a, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    type Item = &'b mut T;
+    type IntoIter = std::collections::vec_deque::IterMut<'b, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.heap.elements.iter_mut()
+    }
+}
+
+impl<T, S> Drop for IterMut<'_, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    fn drop(&mut self) {
+        self.heap.rebuild();
+    }
+}
+
+pub struct ElementsMut<'a, T, S = RandomState>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    heap: &'a mut BinaryHeap<T, S>,
+}
+
+// `elements` was made contiguous when the guard was handed out and nothing can push or pop while
+// it is alive, so the first slice is all of it.
+impl<T, S> std::ops::Deref for ElementsMut<'_, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.heap.elements.as_slices().0
+    }
+}
+
+impl<T, S> std::ops::DerefMut for ElementsMut<'_, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.heap.elements.as_mut_slices().0
+    }
+}
+
+impl<T, S> Drop for ElementsMut<'_, T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    fn drop(&mut self) {
+        self.heap.rebuild();
+    }
+}
+
+// A stored element ordered by priority (higher priority is greater) so that it can go into
+// std's max heap. Used to explore the heap's top without mutating it.
+struct Candidate<'a, T> {
+    element: &'a T,
+    insertion: u64,
+    index: usize,
+    kind: HeapKind,
+    stable: bool,
+    total_cmp: Option<TotalCmp<T>>,
+}
+
+impl<T: std::cmp::PartialOrd> Ord for Candidate<'_, T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (
+            self.kind
+                .prioritizes_with(self.total_cmp, self.element, other.element),
+            self.kind
+                .prioritizes_with(self.total_cmp, other.element, self.element),
+        ) {
+            (true, false) => std::cmp::Ordering::Greater,
+            (false, true) => std::cmp::Ordering::Less,
+            _ if self.stable => other.insertion.cmp(&self.insertion),
+            _ => std::cmp::Ordering::Equal,
+        }
+    }
+}
+
+impl<T: std::cmp::PartialOrd> PartialOrd for Candidate<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: std::cmp::PartialOrd> PartialEq for Candidate<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl<T: std::cmp::PartialOrd> Eq for Candidate<'_, T> {}
+
+fn sift_down_slice<T: std::cmp::PartialOrd>(items: &mut [T], start_ind: usize, kind: &HeapKind) {
+    let mut current = start_ind;
+    loop {
+        let (left, right) = (2 * current + 1, 2 * current + 2);
+        if left >= items.len() {
+            break;
+        }
+        let child = if right < items.len() && !kind.prioritizes(&items[left], &items[right]) {
+            right
+        } else {
+            left
+        };
+        if kind.prioritizes(&items[current], &items[child]) {
+            break;
+        }
+        items.swap(current, child);
+        current = child;
+    }
 }
 
 fn even(num: usize) -> bool {
-    num % 2 == 0
+    num.is_multiple_of(2)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::Rng;
     #[test]
     fn test_parent_and_child_indices() {
         let heap = BinaryHeap::<i32>::heapify(&[4, 4, 8, 9, 5, 12, 11, 13], HeapKind::Min);
@@ -361,4 +2636,240 @@ mod tests {
         assert_eq!(heap.children_indices(3), [Some(7), None]);
         assert_eq!(heap.children_indices(4), [None, None]);
     }
+
+    #[test]
+    fn test_swap_equal_elements() {
+        let mut heap = BinaryHeap::<i32>::heapify(&[5, 5, 1, 5, 5], HeapKind::Min);
+        let mut indices_of_five = heap.get_index(&5).unwrap().to_vec();
+        indices_of_five.sort();
+        assert_eq!(indices_of_five, vec![1, 2, 3, 4]);
+
+        heap.swap_elements(1, 4);
+        heap.swap_elements(3, 2);
+        let mut swapped_indices = heap.get_index(&5).unwrap().to_vec();
+        swapped_indices.sort();
+        assert_eq!(swapped_indices, indices_of_five);
+        assert_eq!(heap.get_index(&1), Some(&[0][..]));
+
+        assert_eq!(heap.remove_object(&5), Some(5));
+        assert_eq!(heap.remove_object(&5), Some(5));
+        let mut remaining_indices = heap.get_index(&5).unwrap().to_vec();
+        remaining_indices.sort();
+        assert_eq!(remaining_indices, vec![1, 2]);
+        assert_eq!(heap.into_sorted_vec(), vec![1, 5, 5]);
+    }
+
+    fn assert_table_consistent<T, S>(heap: &BinaryHeap<T, S>)
+    where
+        T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug,
+        S: BuildHasher,
+    {
+        let tracked_indices: usize = heap.element_indices.values().map(Vec::len).sum();
+        assert_eq!(tracked_indices, heap.len());
+        (0..heap.len()).for_each(|ind| {
+            assert!(heap
+                .get_index(heap.element_at(ind).unwrap())
+                .unwrap()
+                .contains(&ind));
+        });
+    }
+
+    #[test]
+    fn test_extract_steps_keep_table_consistent() {
+        let mut heap =
+            BinaryHeap::<i32>::heapify(&[4, 4, 8, 3, 9, 5, 12, 11, 13, 3], HeapKind::Min);
+        while let Some(last_element_index) = heap.len().checked_sub(1) {
+            heap.swap_elements(0, last_element_index);
+            assert_table_consistent(&heap);
+            heap.pop_back_tracked();
+            assert_table_consistent(&heap);
+            if !heap.is_empty() {
+                heap.bubble_down(0);
+                assert_table_consistent(&heap);
+            }
+        }
+        assert!(heap.element_indices.values().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn test_extract_stress_table_consistency() {
+        let mut rng = rand::thread_rng();
+        let mut heap = BinaryHeap::<i32>::new(HeapKind::Max);
+        for _ in 0..2000 {
+            if rng.gen_bool(0.6) {
+                heap.insert(rng.gen_range(0..100)).unwrap();
+            } else {
+                heap.extract_object();
+            }
+            assert_table_consistent(&heap);
+        }
+    }
+
+    #[test]
+    fn test_with_full() {
+        let mut heap = BinaryHeap::<i32, fnv::FnvBuildHasher>::with_full(
+            1000,
+            HeapKind::Max,
+            Default::default(),
+        );
+        assert!(heap.elements.capacity() >= 1000);
+        assert!(heap.element_indices.capacity() >= 1000);
+
+        (0..1000).for_each(|item| heap.insert(item).unwrap());
+        assert!(heap.elements.capacity() >= 1000);
+        assert_eq!(heap.peek(), Some(&999));
+        assert_eq!(heap.extract_object(), Some(999));
+        assert_eq!(heap.get_index(&998), Some(&[0][..]));
+
+        // A hasher without a Default impl
+        struct SeededHasher(RandomState);
+        impl BuildHasher for SeededHasher {
+            type Hasher = std::collections::hash_map::DefaultHasher;
+            fn build_hasher(&self) -> Self::Hasher {
+                self.0.build_hasher()
+            }
+        }
+        let mut heap = BinaryHeap::with_full(4, HeapKind::Min, SeededHasher(RandomState::new()));
+        [3, 1, 2]
+            .iter()
+            .for_each(|item| heap.insert(*item).unwrap());
+        assert_eq!(heap.remove_object(&2), Some(2));
+        assert_eq!(heap.into_sorted_vec(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_remove_object_compacts_table() {
+        let mut heap = BinaryHeap::<i32>::new(HeapKind::Min);
+        (0..1000).for_each(|item| heap.insert(item).unwrap());
+        assert_eq!(heap.element_indices.len(), 1000);
+
+        // Up to the threshold the emptied entries stay around.
+        (0..750).for_each(|item| assert_eq!(heap.remove_object(&item), Some(item)));
+        assert_eq!(heap.element_indices.len(), 1000);
+        assert_eq!(heap.empty_buckets, 750);
+
+        assert_eq!(heap.remove_object(&750), Some(750));
+        assert_eq!(heap.element_indices.len(), 249);
+        assert_eq!(heap.empty_buckets, 0);
+        assert_table_consistent(&heap);
+        assert_eq!(heap.into_sorted_vec(), (751..1000).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_repair_index() {
+        let mut heap = BinaryHeap::<i32>::new(HeapKind::Min);
+        (0..10).for_each(|item| heap.insert(item).unwrap());
+        assert_eq!(heap.repair_index(), 0);
+
+        let hash = |item: i32| BinaryHeap::<i32>::hash_value(&heap.hash_builder, &item);
+        let [hash_of_3, hash_of_5, hash_of_7, hash_of_42] = [3, 5, 7, 42].map(hash);
+        // Drops 3's index (1), points 5 at the wrong slot (one missing, one bogus: 2), lists 7
+        // twice (1) and adds an entry for a value that isn't stored (1).
+        heap.element_indices.get_mut(&hash_of_3).unwrap().clear();
+        *heap.element_indices.get_mut(&hash_of_5).unwrap() = vec![0];
+        heap.element_indices.get_mut(&hash_of_7).unwrap().push(7);
+        heap.element_indices.insert(hash_of_42, vec![1]);
+        assert!(!heap.validate());
+
+        assert_eq!(heap.repair_index(), 5);
+        assert_table_consistent(&heap);
+        assert!(heap.validate());
+        assert_eq!(heap.repair_index(), 0);
+    }
+
+    #[test]
+    fn test_cached_hashes_stay_in_sync() {
+        let mut rng = rand::thread_rng();
+        let mut heap = BinaryHeap::<i32>::with_full(0, HeapKind::Max, RandomState::new());
+        let hashes_match = |heap: &BinaryHeap<i32>| {
+            heap.hashes.len() == heap.len()
+                && heap
+                    .elements
+                    .iter()
+                    .zip(&heap.hashes)
+                    .all(|(element, hash_value)| {
+                        BinaryHeap::<i32>::hash_value(&heap.hash_builder, element) == *hash_value
+                    })
+        };
+        for step in 0..2000 {
+            let value = rng.gen_range(0..100);
+            match step % 7 {
+                0 | 1 => heap.insert(value).unwrap(),
+                2 => {
+                    heap.extract_object();
+                }
+                3 => {
+                    heap.remove_object(&value);
+                }
+                4 => {
+                    heap.update(&value, |item| *item += 7);
+                }
+                5 => {
+                    if let Some(mut root) = heap.peek_mut() {
+                        *root -= 3;
+                    }
+                }
+                _ => heap.extend(vec![value, value + 1]),
+            }
+            assert!(hashes_match(&heap));
+            assert!(heap.validate());
+        }
+        heap.truncate(10);
+        assert!(hashes_match(&heap));
+    }
+
+    #[test]
+    fn test_ensure_valid() {
+        let mut heap = BinaryHeap::<i32>::heapify(&[1, 2, 3, 4, 5, 6, 7, 8, 9], HeapKind::Min);
+        assert!(!heap.ensure_valid());
+
+        // Breaks the heap property and leaves the table and cached hash pointing at the old value.
+        heap.elements[8] = 0;
+        assert!(heap.ensure_valid());
+        assert!(heap.validate());
+        assert_eq!(heap.peek(), Some(&0));
+        assert!(heap.get_index(&0).is_some() && heap.get_index(&9).is_none());
+        assert!(!heap.ensure_valid());
+    }
+
+    #[test]
+    fn test_fix_at() {
+        let mut heap = BinaryHeap::<i32>::heapify(&(0..20).collect::<Vec<i32>>(), HeapKind::Min);
+        heap.elements[2] = 50;
+        heap.elements[17] = -1;
+        heap.fix_at(&[17, 2]);
+        assert!(heap.validate());
+        assert_eq!(heap.peek(), Some(&-1));
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let data: Vec<i32> = (0..40).map(|_| rng.gen_range(0..30)).collect();
+            let mut heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Max);
+            let dirty: Vec<usize> = (0..4).map(|_| rng.gen_range(0..data.len())).collect();
+            dirty
+                .iter()
+                .for_each(|ind| heap.elements[*ind] = rng.gen_range(0..30));
+            heap.fix_at(&dirty);
+            assert!(heap.validate());
+        }
+    }
+
+    #[test]
+    fn test_first_violation() {
+        let mut heap = BinaryHeap::<i32>::heapify(&[1, 2, 3, 4, 5, 6, 7, 8, 9], HeapKind::Min);
+        assert_eq!(heap.first_violation(), None);
+
+        // 2 at index 1 now exceeds its children 4 and 5
+        heap.elements[1] = 7;
+        assert_eq!(heap.first_violation(), Some(1));
+
+        heap.elements[1] = 2;
+        // 9 at index 8 now beats its parent 4
+        heap.elements[8] = 0;
+        assert_eq!(heap.first_violation(), Some(3));
+        assert_eq!(
+            BinaryHeap::<i32>::new(HeapKind::Max).first_violation(),
+            None
+        );
+    }
 }
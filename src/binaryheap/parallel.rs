@@ -0,0 +1,212 @@
+use super::{BinaryHeap, HeapKind};
+use ::core::hash::BuildHasher;
+use hashbrown::HashMap;
+use rayon::prelude::*;
+
+// Below this many elements splitting the build across threads isn't worth it.
+const PARALLEL_THRESHOLD: usize = 1 << 14;
+// Deepest level whose subtrees get handed out as separate tasks.
+const MAX_SPLIT_DEPTH: u32 = 12;
+
+impl<T, S> BinaryHeap<T, S>
+where
+    T: std::cmp::PartialOrd
+        + Clone
+        + std::hash::Hash
+        + std::cmp::Eq
+        + std::fmt::Debug
+        + Send
+        + Sync,
+    S: BuildHasher + Default + Sync,
+{
+    // Parallel counterpart of heapify. Subtrees hanging off the same level are independent of each
+    // other, so they are heapified concurrently before the levels above them get sifted down
+    // sequentially. The build works on positions into `items`, which are only cloned once at the end.
+    // The index table is assembled from per thread partial maps.
+    pub fn par_heapify(items: &[T], kind: HeapKind) -> Self {
+        let positions = heap_positions(items, &kind);
+        let mut heap = Self::new_with_capacity(kind, 0);
+        heap.elements = positions
+            .par_iter()
+            .map(|source| items[*source].clone())
+            .collect::<Vec<T>>()
+            .into();
+        heap.insertion_order = positions.iter().map(|source| *source as u64).collect();
+        heap.next_insertion = items.len() as u64;
+        heap.hashes = heap
+            .elements
+            .par_iter()
+            .map(|element| Self::hash_value(&heap.hash_builder, element))
+            .collect::<Vec<u64>>()
+            .into();
+        heap.element_indices = heap.par_build_table();
+        heap
+    }
+
+    fn par_build_table(&self) -> HashMap<u64, Vec<usize>> {
+        self.hashes
+            .par_iter()
+            .enumerate()
+            .fold(HashMap::new, |mut partial, (ind, hash_value)| {
+                partial
+                    .entry(*hash_value)
+                    .or_insert_with(Vec::new)
+                    .push(ind);
+                partial
+            })
+            .reduce(HashMap::new, |mut merged, partial| {
+                partial.into_iter().for_each(|(hash_value, mut indices)| {
+                    merged
+                        .entry(hash_value)
+                        .or_insert_with(Vec::new)
+                        .append(&mut indices)
+                });
+                merged
+            })
+    }
+}
+
+impl<T, S> BinaryHeap<T, S>
+where
+    T: std::cmp::PartialOrd + Clone + std::hash::Hash + std::cmp::Eq + std::fmt::Debug + Send,
+    S: BuildHasher + Send,
+{
+    // Drains the heap in priority order as runs of `chunk` elements (the last one may be shorter)
+    // that get processed in parallel. Popping is inherently sequential, but every chunk is handed
+    // out as soon as it has been popped, so the first chunks are being worked on while the rest
+    // of the heap is still draining. Each chunk is in priority order and the chunks are popped in
+    // order, but the workers may see them in any order.
+    // Panics if `chunk` is 0.
+    pub fn par_drain_sorted(mut self, chunk: usize) -> impl ParallelIterator<Item = Vec<T>> {
+        assert!(chunk > 0, "chunk size must be positive");
+        std::iter::from_fn(move || {
+            let batch: Vec<T> = (0..chunk).map_while(|_| self.extract_object()).collect();
+            Some(batch).filter(|batch| !batch.is_empty())
+        })
+        .par_bridge()
+    }
+}
+
+// Returns, for every slot of the heap, the index into `items` of the element that belongs there.
+fn heap_positions<T: PartialOrd + Sync>(items: &[T], kind: &HeapKind) -> Vec<usize> {
+    let mut positions: Vec<usize> = (0..items.len()).collect();
+    if items.len() < PARALLEL_THRESHOLD {
+        heapify_positions(&mut positions, items, kind);
+        return positions;
+    }
+
+    let split_depth = ((rayon::current_num_threads() * 4).next_power_of_two())
+        .trailing_zeros()
+        .min(MAX_SPLIT_DEPTH);
+    let first_root = (1 << split_depth) - 1;
+    let subtrees: Vec<(Vec<usize>, Vec<usize>)> = (first_root..2 * first_root + 1)
+        .into_par_iter()
+        .map(|root| {
+            let slots = subtree_slots(root, items.len());
+            let mut subtree = slots.clone();
+            heapify_positions(&mut subtree, items, kind);
+            (slots, subtree)
+        })
+        .collect();
+    subtrees.into_iter().for_each(|(slots, subtree)| {
+        slots
+            .into_iter()
+            .zip(subtree)
+            .for_each(|(slot, source)| positions[slot] = source);
+    });
+
+    for ind in (0..first_root).rev() {
+        sift_down_positions(&mut positions, ind, items, kind);
+    }
+    positions
+}
+
+// The descendants of `root` on every level below it form a contiguous run of slots, and listing
+// them level by level gives the subtree in its own implicit heap layout.
+fn subtree_slots(root: usize, len: usize) -> Vec<usize> {
+    let mut slots = vec![];
+    let (mut level_start, mut level_width) = (root, 1);
+    while level_start < len {
+        slots.extend(level_start..(level_start + level_width).min(len));
+        level_start = 2 * level_start + 1;
+        level_width *= 2;
+    }
+    slots
+}
+
+fn heapify_positions<T: PartialOrd>(positions: &mut [usize], items: &[T], kind: &HeapKind) {
+    for ind in (0..positions.len() / 2).rev() {
+        sift_down_positions(positions, ind, items, kind);
+    }
+}
+
+fn sift_down_positions<T: PartialOrd>(
+    positions: &mut [usize],
+    start_ind: usize,
+    items: &[T],
+    kind: &HeapKind,
+) {
+    let mut current = start_ind;
+    loop {
+        let (left, right) = (2 * current + 1, 2 * current + 2);
+        if left >= positions.len() {
+            break;
+        }
+        let child = if right < positions.len()
+            && !kind.prioritizes(&items[positions[left]], &items[positions[right]])
+        {
+            right
+        } else {
+            left
+        };
+        if kind.prioritizes(&items[positions[current]], &items[positions[child]]) {
+            break;
+        }
+        positions.swap(current, child);
+        current = child;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_par_heapify_matches_heapify() {
+        let mut rng = thread_rng();
+        for size in [0, 1, 100, PARALLEL_THRESHOLD * 4 + 17] {
+            let data: Vec<i32> = (0..size).map(|_| rng.gen_range(-1000..1000)).collect();
+            let heap = BinaryHeap::<i32>::par_heapify(&data, HeapKind::Max);
+            assert_eq!(heap.len(), data.len());
+            (0..heap.len()).for_each(|ind| assert!(heap.verify_heap_property(ind)));
+            data.iter().for_each(|item| {
+                heap.get_index(item).unwrap().iter().for_each(|index| {
+                    assert_eq!(heap.element_at(*index), Some(item));
+                });
+            });
+
+            let sequential = BinaryHeap::<i32>::heapify(&data, HeapKind::Max);
+            assert_eq!(heap.into_sorted_vec(), sequential.into_sorted_vec());
+        }
+    }
+
+    #[test]
+    fn test_par_drain_sorted() {
+        let mut rng = thread_rng();
+        let data: Vec<i32> = (0..10_000).map(|_| rng.gen_range(-1000..1000)).collect();
+        let heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Min);
+
+        let mut chunks: Vec<Vec<i32>> = heap.par_drain_sorted(64).collect();
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 64));
+        assert_eq!(chunks.len(), data.len().div_ceil(64));
+        assert_eq!(chunks.iter().filter(|chunk| chunk.len() < 64).count(), 1);
+
+        // Chunks cover consecutive runs of the extraction order, but arrive in any order.
+        chunks.sort_by_key(|chunk| (chunk[0], chunk[chunk.len() - 1]));
+
+        let mut sorted = data;
+        sorted.sort_unstable();
+        assert_eq!(chunks.concat(), sorted);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{BinaryHeap, GrowthPolicy, HeapKind};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+// Collects the heap's configuration so new options don't each need their own constructor.
+// Defaults to an unbounded, non stable Min heap with no preallocated capacity.
+pub struct BinaryHeapBuilder<T, S = RandomState> {
+    kind: HeapKind,
+    capacity: usize,
+    hash_builder: S,
+    stable: bool,
+    bound: Option<usize>,
+    growth_policy: GrowthPolicy,
+    marker: PhantomData<T>,
+}
+
+impl<T, S> BinaryHeapBuilder<T, S>
+where
+    T: PartialOrd + Clone + Hash + Eq + std::fmt::Debug,
+    S: BuildHasher + Default,
+{
+    pub fn new() -> Self {
+        Self {
+            kind: HeapKind::Min,
+            capacity: 0,
+            hash_builder: S::default(),
+            stable: false,
+            bound: None,
+            growth_policy: GrowthPolicy::Doubling,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn kind(mut self, kind: HeapKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn hasher(mut self, hash_builder: S) -> Self {
+        self.hash_builder = hash_builder;
+        self
+    }
+
+    // Equal priority elements are extracted in the order they were inserted.
+    pub fn stable(mut self, stable: bool) -> Self {
+        self.stable = stable;
+        self
+    }
+
+    // Keeps at most `bound` elements, evicting the lowest priority one to make room for a better one.
+    pub fn bound(mut self, bound: usize) -> Self {
+        self.bound = Some(bound);
+        self
+    }
+
+    pub fn growth_policy(mut self, growth_policy: GrowthPolicy) -> Self {
+        self.growth_policy = growth_policy;
+        self
+    }
+
+    pub fn build(self) -> BinaryHeap<T, S> {
+        let mut heap = BinaryHeap::with_options(
+            self.kind,
+            self.capacity,
+            self.hash_builder,
+            self.stable,
+            self.bound,
+        );
+        heap.set_growth_policy(self.growth_policy);
+        heap
+    }
+}
+
+impl<T, S> Default for BinaryHeapBuilder<T, S>
+where
+    T: PartialOrd + Clone + Hash + Eq + std::fmt::Debug,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
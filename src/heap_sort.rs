@@ -0,0 +1,37 @@
+use crate::{BinaryHeap, HeapKind};
+use std::hash::Hash;
+
+// Sorts anything iterable by pushing it through a BinaryHeap and draining it.
+pub trait HeapSortExt: IntoIterator + Sized
+where
+    Self::Item: PartialOrd + Clone + Hash + Eq + std::fmt::Debug,
+{
+    // Ascending order
+    fn heap_sort_min(self) -> Vec<Self::Item> {
+        heap_sort(self, HeapKind::Min)
+    }
+
+    // Descending order
+    fn heap_sort_max(self) -> Vec<Self::Item> {
+        heap_sort(self, HeapKind::Max)
+    }
+}
+
+impl<I> HeapSortExt for I
+where
+    I: IntoIterator,
+    I::Item: PartialOrd + Clone + Hash + Eq + std::fmt::Debug,
+{
+}
+
+fn heap_sort<I>(items: I, kind: HeapKind) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    I::Item: PartialOrd + Clone + Hash + Eq + std::fmt::Debug,
+{
+    let mut heap = BinaryHeap::<I::Item>::new(kind);
+    items
+        .into_iter()
+        .for_each(|item| heap.insert_uncapped(item));
+    heap.into_sorted_vec_fast()
+}
@@ -1,7 +1,31 @@
 mod binaryheap;
+mod builder;
+mod heap_sort;
+mod priority_queue;
+mod sync_heap;
 
 pub use binaryheap::BinaryHeap;
+pub use binaryheap::CapacityExceeded;
+pub use binaryheap::CheckedIndex;
+pub use binaryheap::Cursor;
+pub use binaryheap::DuplicatePolicy;
+pub use binaryheap::ElementsMut;
+pub use binaryheap::GrowthPolicy;
 pub use binaryheap::HeapKind;
+pub use binaryheap::HeapViolation;
+pub use binaryheap::InsertResult;
+pub use binaryheap::IntoIter;
+pub use binaryheap::IterMut;
+pub use binaryheap::MemoryReport;
+pub use binaryheap::Merge;
+pub use binaryheap::Neighbors;
+pub use binaryheap::PeekMut;
+pub use binaryheap::ReversedView;
+pub use binaryheap::DEFAULT_MIN_CAPACITY;
+pub use builder::BinaryHeapBuilder;
+pub use heap_sort::HeapSortExt;
+pub use priority_queue::{Entry, OccupiedEntry, PriorityQueue, VacantEntry};
+pub use sync_heap::SyncHeap;
 
 #[cfg(test)]
 mod tests {
@@ -13,7 +37,7 @@ mod tests {
 
     fn insert_seed_data(heap: &mut BinaryHeap<i32>, data: &[i32]) {
         data.iter().for_each(|item| {
-            heap.insert(*item);
+            heap.insert(*item).unwrap();
         });
     }
 
@@ -131,4 +155,2148 @@ mod tests {
         assert_eq!(None, heap.remove_object(&5));
         assert_eq!(heap.extract_object(), None);
     }
+
+    #[test]
+    fn test_verify_index_holds() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[1, 2, 3, 4]);
+
+        let cached_index = heap.get_index(&2).unwrap()[0];
+        assert!(heap.is_valid_index(cached_index));
+        assert!(heap.verify_index_holds(cached_index, &2));
+        assert!(!heap.is_valid_index(4));
+
+        heap.extract_object();
+        assert!(heap.is_valid_index(cached_index));
+        assert!(!heap.verify_index_holds(cached_index, &2));
+        assert!(!heap.verify_index_holds(10, &2));
+    }
+
+    #[test]
+    fn test_extract_while_root() {
+        let mut timers = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut timers, &[50, 10, 40, 20, 70, 30, 60]);
+        let now = 40;
+
+        let due = timers.extract_while_root(|timestamp| *timestamp <= now);
+        assert_eq!(due, vec![10, 20, 30, 40]);
+        assert_eq!(timers.len(), 3);
+        assert_eq!(timers.peek(), Some(&50));
+
+        assert!(timers
+            .extract_while_root(|timestamp| *timestamp <= now)
+            .is_empty());
+        assert_eq!(timers.extract_while_root(|_| true), vec![50, 60, 70]);
+        assert!(timers.is_empty());
+    }
+
+    #[test]
+    fn test_heapify_refs() {
+        let words: Vec<String> = ["pear", "apple", "fig", "banana", "apple"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let mut heap: BinaryHeap<&String> = BinaryHeap::heapify_refs(&words, HeapKind::Min);
+
+        assert!(std::ptr::eq(*heap.peek().unwrap(), &words[1]));
+
+        let banana = "banana".to_string();
+        assert_eq!(heap.remove_object(&&banana), Some(&words[3]));
+        assert_eq!(heap.remove_object(&&banana), None);
+
+        let mut extracted = vec![];
+        while let Some(word) = heap.extract_object() {
+            extracted.push(word.as_str());
+        }
+        assert_eq!(extracted, vec!["apple", "apple", "fig", "pear"]);
+    }
+
+    #[test]
+    fn test_into_sorted_vec_fast() {
+        let mut rng = thread_rng();
+        let data: Vec<i32> = (0..10000).map(|_| rng.gen_range(0..50)).collect();
+        let mut expected = data.clone();
+        expected.sort();
+
+        let heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Min);
+        let fast_heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Min);
+        assert_eq!(heap.into_sorted_vec(), expected);
+        assert_eq!(fast_heap.into_sorted_vec_fast(), expected);
+
+        expected.reverse();
+        let heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Max);
+        let fast_heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Max);
+        assert_eq!(heap.into_sorted_vec(), expected);
+        assert_eq!(fast_heap.into_sorted_vec_fast(), expected);
+
+        assert!(BinaryHeap::<i32>::new(HeapKind::Min)
+            .into_sorted_vec_fast()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_extremes() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        assert_eq!(heap.extremes(), None);
+
+        heap.insert(7).unwrap();
+        assert_eq!(heap.extremes(), Some((&7, &7)));
+
+        insert_seed_data(&mut heap, &[4, 4, 8, 3, 9, 5, 12, 11, 13]);
+        assert_eq!(heap.extremes(), Some((&3, &13)));
+
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[4, 4, 8, 3, 9, 5, 12, 11, 13]);
+        assert_eq!(heap.extremes(), Some((&13, &3)));
+    }
+
+    #[test]
+    fn test_heap_sort_ext() {
+        let mut rng = thread_rng();
+        let data: Vec<i32> = (&mut rng).sample_iter(Standard).take(1000).collect();
+        let mut expected = data.clone();
+        expected.sort();
+
+        assert_eq!(data.clone().heap_sort_min(), expected);
+        expected.reverse();
+        assert_eq!(
+            data.iter().heap_sort_max(),
+            expected.iter().collect::<Vec<_>>()
+        );
+        assert!(Vec::<i32>::new().heap_sort_min().is_empty());
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let mut rng = thread_rng();
+        let mut data: Vec<i32> = (&mut rng).sample_iter(Standard).take(1000).collect();
+        let heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Min);
+        data.sort();
+
+        let mut extracted = vec![];
+        for item in heap {
+            extracted.push(item);
+        }
+        assert_eq!(extracted, data);
+
+        let heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Max);
+        let iter = heap.into_iter();
+        assert_eq!(iter.size_hint(), (1000, Some(1000)));
+        data.reverse();
+        assert_eq!(iter.collect::<Vec<_>>(), data);
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Task {
+        priority: u32,
+        name: &'static str,
+    }
+
+    impl PartialOrd for Task {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            self.priority.partial_cmp(&other.priority)
+        }
+    }
+
+    #[test]
+    fn test_builder_bounded_stable() {
+        let mut heap: BinaryHeap<Task> = BinaryHeapBuilder::new()
+            .kind(HeapKind::Max)
+            .capacity(8)
+            .stable(true)
+            .bound(3)
+            .build();
+        let tasks = [(2, "a"), (5, "b"), (5, "c"), (1, "d"), (5, "e"), (9, "f")];
+        tasks.iter().for_each(|(priority, name)| {
+            heap.insert(Task {
+                priority: *priority,
+                name,
+            })
+            .unwrap()
+        });
+
+        assert_eq!(heap.len(), 3);
+        let extracted: Vec<&str> = heap.into_iter().map(|task| task.name).collect();
+        assert_eq!(extracted, vec!["f", "b", "c"]);
+
+        let mut unbounded: BinaryHeap<Task> = BinaryHeapBuilder::default()
+            .kind(HeapKind::Min)
+            .stable(true)
+            .build();
+        ["a", "b", "c", "d", "e"]
+            .iter()
+            .for_each(|name| unbounded.insert(Task { priority: 1, name }).unwrap());
+        let extracted: Vec<&str> = unbounded.into_iter().map(|task| task.name).collect();
+        assert_eq!(extracted, vec!["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn test_remove_first_matching() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        let data = [8, 3, -4, 9, 5, -12, 11, 2, 13];
+        insert_seed_data(&mut heap, &data);
+
+        let removed = heap.remove_first_matching(|item| *item < 0).unwrap();
+        assert!(removed == -4 || removed == -12);
+        assert_eq!(heap.len(), data.len() - 1);
+        assert_eq!(heap.remove_first_matching(|item| *item > 100), None);
+
+        let remaining = heap.remove_first_matching(|item| *item < 0).unwrap();
+        assert_eq!(removed + remaining, -16);
+        assert_eq!(heap.remove_first_matching(|item| *item < 0), None);
+        assert_eq!(heap.into_sorted_vec(), vec![2, 3, 5, 8, 9, 11, 13]);
+    }
+
+    #[test]
+    fn test_empty_and_single_element_heaps() {
+        let mut heap = BinaryHeap::<i32>::new(HeapKind::Min);
+        assert_eq!(heap.remove_object(&1), None);
+        assert_eq!(heap.remove_first_matching(|_| true), None);
+        assert_eq!(heap.extract_object(), None);
+        assert_eq!(heap.extremes(), None);
+        assert!(heap.extract_while_root(|_| true).is_empty());
+
+        heap.insert(1).unwrap();
+        assert_eq!(heap.remove_object(&2), None);
+        assert_eq!(heap.remove_object(&1), Some(1));
+        assert_eq!(heap.remove_object(&1), None);
+        assert!(heap.is_empty());
+
+        heap.insert(1).unwrap();
+        assert_eq!(heap.extract_object(), Some(1));
+        assert_eq!(heap.extract_object(), None);
+        assert_eq!(heap.get_index(&1), None);
+
+        heap.insert(1).unwrap();
+        assert_eq!(heap.remove_first_matching(|item| *item == 1), Some(1));
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut rng = thread_rng();
+        let mut data: Vec<i32> = (0..1000).map(|_| rng.gen_range(-500..500)).collect();
+        let mut heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Min);
+
+        for item in &mut heap.iter_mut() {
+            *item *= -1;
+        }
+
+        data.iter_mut().for_each(|item| *item *= -1);
+        data.iter().for_each(|item| {
+            heap.get_index(item).unwrap().iter().for_each(|index| {
+                assert_eq!(heap.element_at(*index), Some(item));
+            });
+        });
+        data.sort();
+        assert_eq!(heap.into_sorted_vec(), data);
+    }
+
+    #[test]
+    fn test_map() {
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[4, -8, 3, 9, 5, -12, 11, 2, 13]);
+
+        let doubled = heap.map(|item| item * 2);
+        assert_eq!(doubled.peek(), Some(&26));
+        assert_eq!(
+            doubled.get_index(&-24).map(|indices| indices.len()),
+            Some(1)
+        );
+        assert_eq!(
+            doubled.into_sorted_vec(),
+            vec![26, 22, 18, 10, 8, 6, 4, -16, -24]
+        );
+
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[4, -8, 3]);
+        let negated: BinaryHeap<i64> = heap.map(|item| -(item as i64));
+        assert_eq!(negated.into_sorted_vec(), vec![-4, -3, 8]);
+    }
+
+    #[test]
+    fn test_contains_all() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[4, 4, 3]);
+
+        assert!(heap.contains_all(vec![4, 4]));
+        assert!(heap.contains_all(vec![3, 4, 4]));
+        assert!(heap.contains_all(vec![]));
+        assert!(!heap.contains_all(vec![4, 4, 4]));
+        assert!(!heap.contains_all(vec![5]));
+
+        heap.remove_object(&4);
+        assert!(heap.contains_all(vec![4, 3]));
+        assert!(!heap.contains_all(vec![4, 4]));
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut data: Vec<i32> = (0..20).collect();
+        data.shuffle(&mut thread_rng());
+        let mut heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Min);
+
+        heap.truncate(25);
+        assert_eq!(heap.len(), 20);
+
+        heap.truncate(5);
+        assert_eq!(heap.len(), 5);
+        assert!(heap.contains_all(vec![0, 1, 2, 3, 4]));
+        assert_eq!(heap.get_index(&5), None);
+        heap.insert(-1).unwrap();
+        assert_eq!(heap.into_sorted_vec(), vec![-1, 0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[10, 8, 9, 3, 4]);
+        assert!(heap.verify_index_holds(4, &4));
+        assert!(heap.verify_index_holds(1, &8));
+
+        // 4 -> 9 is now larger than its parent 8 and has to bubble up.
+        assert!(heap.update(&4, |item| *item += 5));
+        assert!(heap.verify_index_holds(1, &9));
+        assert!(heap.verify_index_holds(4, &8));
+        assert_eq!(heap.get_index(&4), None);
+        assert_eq!(heap.get_index(&8), Some(&[4][..]));
+        assert_eq!(heap.get_index(&9).unwrap().len(), 2);
+
+        assert!(heap.update(&3, |item| *item = 20));
+        assert_eq!(heap.peek(), Some(&20));
+        assert!(!heap.update(&42, |item| *item = 0));
+        assert_eq!(heap.into_sorted_vec(), vec![20, 10, 9, 9, 8]);
+    }
+
+    #[test]
+    fn test_heap_kind_queries() {
+        let heap = BinaryHeap::<i32>::new(HeapKind::Min);
+        assert!(heap.is_min_heap());
+        assert!(!heap.is_max_heap());
+
+        let heap = BinaryHeap::<i32>::new(HeapKind::Max);
+        assert!(heap.is_max_heap());
+        assert!(!heap.is_min_heap());
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut rng = thread_rng();
+        let data: Vec<i32> = (&mut rng).sample_iter(Standard).take(1000).collect();
+        let mut expected = data.clone();
+        expected.sort();
+
+        let mut ascending = data.clone();
+        BinaryHeap::<i32>::sort(&mut ascending, HeapKind::Max);
+        assert_eq!(ascending, expected);
+
+        let mut descending = data;
+        BinaryHeap::<i32>::sort(&mut descending, HeapKind::Min);
+        expected.reverse();
+        assert_eq!(descending, expected);
+
+        let mut empty: [i32; 0] = [];
+        BinaryHeap::<i32>::sort(&mut empty, HeapKind::Min);
+        let mut single = [1];
+        BinaryHeap::<i32>::sort(&mut single, HeapKind::Min);
+        assert_eq!(single, [1]);
+    }
+
+    #[test]
+    fn test_counts() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        assert_eq!((heap.count_total(), heap.count_distinct()), (0, 0));
+
+        insert_seed_data(&mut heap, &[4, 4, 4, 3, 3, 5, 9]);
+        assert_eq!(heap.count_total(), 7);
+        assert_eq!(heap.count_distinct(), 4);
+
+        heap.remove_object(&9);
+        heap.remove_object(&4);
+        assert_eq!((heap.count_total(), heap.count_distinct()), (5, 3));
+        heap.extract_object();
+        heap.extract_object();
+        assert_eq!((heap.count_total(), heap.count_distinct()), (3, 2));
+    }
+
+    #[test]
+    fn test_occurrences() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[7, 2, 7, 9, 1, 7]);
+
+        let occurrences: Vec<(usize, &i32)> = heap.occurrences(&7).collect();
+        assert_eq!(occurrences.len(), 3);
+        occurrences.iter().for_each(|(index, value)| {
+            assert_eq!(**value, 7);
+            assert!(heap.verify_index_holds(*index, &7));
+        });
+        assert_eq!(heap.occurrences(&5).count(), 0);
+    }
+
+    #[test]
+    fn test_checked_insert() {
+        let mut heap: BinaryHeap<i32> = BinaryHeapBuilder::new()
+            .kind(HeapKind::Max)
+            .bound(3)
+            .build();
+        assert_eq!(heap.checked_insert(5), InsertResult::Accepted);
+        assert_eq!(heap.checked_insert(3), InsertResult::Accepted);
+        assert_eq!(heap.checked_insert(8), InsertResult::Accepted);
+
+        assert_eq!(heap.checked_insert(1), InsertResult::Rejected(1));
+        assert_eq!(heap.checked_insert(3), InsertResult::Rejected(3));
+        assert_eq!(heap.checked_insert(4), InsertResult::Evicted(3));
+        assert_eq!(heap.checked_insert(10), InsertResult::Evicted(4));
+        assert_eq!(heap.into_sorted_vec(), vec![10, 8, 5]);
+
+        let mut unbounded = BinaryHeap::<i32>::new(HeapKind::Max);
+        assert_eq!(unbounded.checked_insert(1), InsertResult::Accepted);
+    }
+
+    #[test]
+    fn test_extract_range() {
+        let mut data: Vec<i32> = (0..10).collect();
+        data.shuffle(&mut thread_rng());
+        let mut heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Max);
+
+        assert_eq!(heap.extract_range(&3, &7), vec![7, 6, 5, 4, 3]);
+        assert_eq!(heap.len(), 5);
+        assert!(heap.extract_range(&3, &7).is_empty());
+        assert_eq!(heap.get_index(&5), None);
+        assert_eq!(heap.into_sorted_vec(), vec![9, 8, 2, 1, 0]);
+
+        let mut heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Min);
+        assert_eq!(heap.extract_range(&-5, &1), vec![0, 1]);
+        assert_eq!(heap.into_sorted_vec(), vec![2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_checked_index() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[5, 1, 8, 3]);
+        assert_eq!(heap.checked_index(4), None);
+
+        let index_of_eight = heap.get_index(&8).unwrap()[0];
+        let checked = heap.checked_index(index_of_eight).unwrap();
+        assert_eq!(heap.resolve(checked), Some(&8));
+        heap.peek();
+        assert_eq!(heap.resolve(checked), Some(&8));
+
+        let version = heap.version();
+        heap.insert(0).unwrap();
+        assert_ne!(heap.version(), version);
+        assert_eq!(heap.resolve(checked), None);
+
+        let version = heap.version();
+        heap.update(&0, |item| *item = 2);
+        assert_ne!(heap.version(), version);
+        let version = heap.version();
+        heap.remove_object(&3);
+        assert_ne!(heap.version(), version);
+        let version = heap.version();
+        heap.extract_object();
+        assert_ne!(heap.version(), version);
+    }
+
+    #[test]
+    fn test_find_priority() {
+        let mut heap = BinaryHeap::<Task>::new(HeapKind::Max);
+        [(1, "a"), (5, "b"), (3, "c"), (9, "d"), (5, "e"), (2, "f")]
+            .iter()
+            .for_each(|(priority, name)| {
+                heap.insert(Task {
+                    priority: *priority,
+                    name,
+                })
+                .unwrap()
+            });
+
+        let probe = Task {
+            priority: 5,
+            name: "probe",
+        };
+        let found = heap.find_priority(&probe).unwrap();
+        assert_eq!(found.priority, 5);
+        assert_ne!(found, &probe);
+        assert!(heap
+            .find_priority(&Task {
+                priority: 4,
+                name: "probe",
+            })
+            .is_none());
+
+        assert_eq!(heap.any_equal_to_root(), None);
+        heap.insert(Task {
+            priority: 9,
+            name: "g",
+        })
+        .unwrap();
+        assert_eq!(heap.any_equal_to_root().map(|task| task.priority), Some(9));
+        assert_ne!(heap.any_equal_to_root(), heap.peek());
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut data: Vec<i32> = (0..10).collect();
+        data.shuffle(&mut thread_rng());
+        let mut heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Min);
+
+        let worse = heap.split_off(&5);
+        assert!(worse.is_min_heap());
+        assert!(worse.get_index(&3).is_none());
+        assert!(worse.contains_all(vec![6, 7, 8, 9]));
+        assert!(heap.contains_all(vec![0, 1, 2, 3, 4, 5]));
+        assert_eq!(heap.into_sorted_vec(), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(worse.into_sorted_vec(), vec![6, 7, 8, 9]);
+
+        let mut heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Max);
+        let worse = heap.split_off(&5);
+        assert_eq!(heap.into_sorted_vec(), vec![9, 8, 7, 6, 5]);
+        assert_eq!(worse.into_sorted_vec(), vec![4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_snapshot_top() {
+        let mut rng = thread_rng();
+        let data: Vec<i32> = (0..500).map(|_| rng.gen_range(0..100)).collect();
+        let heap = BinaryHeap::<i32>::heapify(&data, HeapKind::Max);
+
+        let snapshot = heap.snapshot_top(20);
+        assert_eq!(heap.len(), 500);
+        assert_eq!(heap.snapshot_top(0), vec![]);
+        assert_eq!(heap.snapshot_top(1000).len(), 500);
+
+        let mut drained = BinaryHeap::<i32>::heapify(&data, HeapKind::Max);
+        let expected: Vec<i32> = (0..20).filter_map(|_| drained.extract_object()).collect();
+        assert_eq!(snapshot, expected);
+        assert_eq!(heap.into_sorted_vec().len(), 500);
+    }
+
+    #[test]
+    fn test_transform_all() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[4, 8, 3, 9, 5, 3]);
+
+        heap.transform_all(|item| item + 100);
+        assert_eq!(heap.get_index(&3), None);
+        assert_eq!(heap.get_index(&103).unwrap().len(), 2);
+        assert_eq!(heap.peek(), Some(&103));
+        assert_eq!(heap.into_sorted_vec(), vec![103, 103, 104, 105, 108, 109]);
+    }
+
+    #[test]
+    fn test_extract_n_if_full() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[9, 0, 8, 1, 7, 2, 6, 3, 5, 4]);
+
+        assert_eq!(heap.extract_n_if_full(3), Some(vec![0, 1, 2]));
+        assert_eq!(heap.extract_n_if_full(100), None);
+        assert_eq!(heap.len(), 7);
+        assert_eq!(heap.extract_n_if_full(7), Some(vec![3, 4, 5, 6, 7, 8, 9]));
+        assert_eq!(heap.extract_n_if_full(1), None);
+        assert_eq!(heap.extract_n_if_full(0), Some(vec![]));
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[4, 4, 4, 3, 3, 5]);
+
+        heap.dedup();
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.count_distinct(), 3);
+        [3, 4, 5].iter().for_each(|item| {
+            assert_eq!(heap.get_index(item).map(|indices| indices.len()), Some(1))
+        });
+        heap.dedup();
+        assert_eq!(heap.into_sorted_vec(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_linear_growth_policy() {
+        let mut heap: BinaryHeap<i32> = BinaryHeapBuilder::new()
+            .growth_policy(GrowthPolicy::Linear(1024))
+            .build();
+        assert_eq!(heap.capacity(), 0);
+
+        let mut capacities = vec![];
+        for item in 0..5000 {
+            heap.insert(item).unwrap();
+            if capacities.last() != Some(&heap.capacity()) {
+                capacities.push(heap.capacity());
+            }
+        }
+        assert_eq!(capacities, vec![1024, 2048, 3072, 4096, 5120]);
+
+        let mut heap = BinaryHeap::<i32>::new(HeapKind::Min);
+        heap.set_growth_policy(GrowthPolicy::Linear(10));
+        (0..25).for_each(|item| heap.insert(item).unwrap());
+        assert_eq!(heap.capacity(), 30);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let heap = BinaryHeap::<i32>::heapify(&[1, 2, 3, 4, 5, 6], HeapKind::Min);
+        // [1, 2, 3, 4, 5, 6] is already a valid min heap, so positions match the input.
+        assert_eq!(
+            heap.neighbors(0),
+            Some(Neighbors {
+                element: &1,
+                parent: None,
+                children: [Some(&2), Some(&3)],
+            })
+        );
+        assert_eq!(
+            heap.neighbors(2),
+            Some(Neighbors {
+                element: &3,
+                parent: Some(&1),
+                children: [Some(&6), None],
+            })
+        );
+        assert_eq!(
+            heap.neighbors(4),
+            Some(Neighbors {
+                element: &5,
+                parent: Some(&2),
+                children: [None, None],
+            })
+        );
+        assert_eq!(heap.neighbors(6), None);
+    }
+
+    #[test]
+    fn test_bulk_remove() {
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[4, 1, 5, 4, 9, 5, 2, 4]);
+
+        let values: std::collections::HashSet<i32> = vec![4, 5, 7].into_iter().collect();
+        assert_eq!(heap.bulk_remove(&values), 5);
+        assert_eq!(heap.len(), 3);
+        assert!(heap.get_index(&4).is_none());
+        assert!(heap.get_index(&5).is_none());
+        assert_eq!(heap.into_sorted_vec(), vec![9, 2, 1]);
+    }
+
+    #[test]
+    fn test_into_ordered_pair() {
+        for kind in [HeapKind::Min, HeapKind::Max] {
+            let mut heap = BinaryHeap::new(kind);
+            insert_seed_data(&mut heap, &[7, 3, 9, 3, 1, 8]);
+
+            let (min, max) = heap.into_ordered_pair();
+            assert!(min.is_min_heap());
+            assert!(max.is_max_heap());
+            assert_eq!(min.into_sorted_vec(), vec![1, 3, 3, 7, 8, 9]);
+            assert_eq!(max.into_sorted_vec(), vec![9, 8, 7, 3, 3, 1]);
+        }
+    }
+
+    #[test]
+    fn test_priority_queue() {
+        let mut queue: PriorityQueue<u32, &str> = PriorityQueue::new(HeapKind::Max);
+        queue.push(2, "write");
+        queue.push(5, "deploy");
+        queue.push(1, "test");
+        queue.push(3, "review");
+        assert_eq!(queue.len(), 4);
+        assert_eq!(queue.peek(), Some((&5, &"deploy")));
+
+        assert!(queue.change_priority(&"test", 4));
+        assert!(!queue.change_priority(&"missing", 9));
+
+        let mut popped = vec![];
+        while let Some(entry) = queue.pop() {
+            popped.push(entry);
+        }
+        assert_eq!(
+            popped,
+            vec![(5, "deploy"), (4, "test"), (3, "review"), (2, "write")]
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_auto_shrink() {
+        let mut heap = BinaryHeap::<i32>::new_with_capacity(HeapKind::Min, 1000);
+        heap.auto_shrink(0.25);
+        (0..1000).for_each(|item| heap.insert(item).unwrap());
+        let peak = heap.capacity();
+
+        while heap.len() > 250 {
+            heap.extract_object();
+            assert_eq!(heap.capacity(), peak);
+        }
+        heap.extract_object();
+        assert!(heap.capacity() < peak);
+        assert!(heap.capacity() >= heap.len());
+        assert_eq!(heap.peek(), Some(&751));
+    }
+
+    #[test]
+    fn test_drain_sorted_into() {
+        let mut out = Vec::with_capacity(16);
+        let mut first = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut first, &[3, 1, 2]);
+        first.drain_sorted_into(&mut out);
+        assert!(first.is_empty());
+
+        let mut second = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut second, &[5, 9, 7]);
+        second.drain_sorted_into(&mut out);
+        assert!(second.is_empty());
+
+        assert_eq!(out, vec![1, 2, 3, 9, 7, 5]);
+        assert_eq!(out.capacity(), 16);
+    }
+
+    #[test]
+    fn test_new_ord() {
+        // Readings below zero are sensor errors that PartialOrd refuses to compare, while Ord
+        // still gives every value a place.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Reading(i32);
+
+        #[allow(clippy::non_canonical_partial_ord_impl)]
+        impl PartialOrd for Reading {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                if self.0 < 0 || other.0 < 0 {
+                    None
+                } else {
+                    Some(self.0.cmp(&other.0))
+                }
+            }
+        }
+
+        impl Ord for Reading {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let mut rng = thread_rng();
+        let mut heap = BinaryHeap::<Reading>::new_ord(HeapKind::Min);
+        let mut data: Vec<i32> = (0..200).map(|_| rng.gen_range(-50..50)).collect();
+        data.iter()
+            .for_each(|item| heap.insert(Reading(*item)).unwrap());
+        assert_eq!(heap.find_priority(&Reading(-60)), None);
+
+        data.sort_unstable();
+        let sorted: Vec<i32> = heap.into_sorted_vec().into_iter().map(|r| r.0).collect();
+        assert_eq!(sorted, data);
+    }
+
+    #[test]
+    fn test_remove_object_last() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[1, 5, 2, 5, 6, 7]);
+        // The 5s sit at index 1 and at its left child, index 3.
+        assert_eq!(heap.element_at(1), Some(&5));
+        assert_eq!(heap.element_at(3), Some(&5));
+
+        assert_eq!(heap.remove_object_last(&5), Some(5));
+        // The upper copy is untouched and the last leaf took the removed slot.
+        assert_eq!(heap.get_index(&5), Some(&[1][..]));
+        assert_eq!(heap.element_at(3), Some(&7));
+        assert_eq!(heap.remove_object_last(&5), Some(5));
+        assert_eq!(heap.remove_object_last(&5), None);
+        assert_eq!(heap.into_sorted_vec(), vec![1, 2, 6, 7]);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut rng = thread_rng();
+        let mut expected: Vec<i32> = (0..100).map(|_| rng.gen_range(0..50)).collect();
+        let mut heap = BinaryHeap::<i32>::new(HeapKind::Min);
+        heap.extend(expected.clone());
+
+        // Small relative to the heap, inserted one by one.
+        let small: Vec<i32> = (0..5).map(|_| rng.gen_range(0..50)).collect();
+        heap.extend(small.clone());
+        expected.extend(small);
+
+        // Large relative to the heap, appended and rebuilt.
+        let large: Vec<i32> = (0..1000).map(|_| rng.gen_range(0..50)).collect();
+        heap.extend(large.clone());
+        expected.extend(large);
+
+        // Forced rebuild, even for a single element.
+        heap.set_extend_rebuild_threshold(0.0);
+        heap.extend(vec![-1]);
+        expected.push(-1);
+
+        assert_eq!(heap.len(), expected.len());
+        assert!(heap.first_violation().is_none());
+        expected.iter().for_each(|item| {
+            heap.get_index(item).unwrap().iter().for_each(|index| {
+                assert_eq!(heap.element_at(*index), Some(item));
+            });
+        });
+        expected.sort_unstable();
+        assert_eq!(heap.into_sorted_vec(), expected);
+    }
+
+    #[test]
+    fn test_rank_of() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[8, 3, 5, 1, 9, 5, 2]);
+        assert_eq!(heap.rank_of(&1), Some(0));
+        assert_eq!(heap.rank_of(&3), Some(2));
+        assert_eq!(heap.rank_of(&5), Some(3));
+        assert_eq!(heap.rank_of(&8), Some(5));
+        assert_eq!(heap.rank_of(&9), Some(6));
+        assert_eq!(heap.rank_of(&4), None);
+    }
+
+    #[test]
+    fn test_from_vec_counting() {
+        let (heap, duplicates) =
+            BinaryHeap::<i32>::from_vec_counting(vec![1, 1, 2, 3, 3, 3], HeapKind::Max);
+        assert_eq!(duplicates, 3);
+        assert_eq!(heap.len(), 6);
+        assert_eq!(heap.into_sorted_vec(), vec![3, 3, 3, 2, 1, 1]);
+
+        let (heap, duplicates) = BinaryHeap::<i32>::from_vec_counting(vec![], HeapKind::Min);
+        assert_eq!(duplicates, 0);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_peek_mut() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[4, 1, 7, 3]);
+        assert!(BinaryHeap::<i32>::new(HeapKind::Min).peek_mut().is_none());
+
+        // Reading through the guard, or writing back the same value, leaves the heap untouched.
+        let version = heap.version();
+        {
+            let mut root = heap.peek_mut().unwrap();
+            assert_eq!(*root, 1);
+            *root = 1;
+        }
+        assert_eq!(heap.version(), version);
+
+        *heap.peek_mut().unwrap() = 5;
+        assert_ne!(heap.version(), version);
+        assert_eq!(heap.peek(), Some(&3));
+        assert!(heap.get_index(&1).is_none());
+        heap.get_index(&5).unwrap().iter().for_each(|index| {
+            assert_eq!(heap.element_at(*index), Some(&5));
+        });
+        assert_eq!(heap.into_sorted_vec(), vec![3, 4, 5, 7]);
+    }
+
+    #[test]
+    fn test_peek_mut_priority_only() {
+        // Equal (and hashed) by name, ordered by priority.
+        #[derive(Debug, Clone, Eq)]
+        struct Job {
+            name: &'static str,
+            priority: u32,
+        }
+
+        impl PartialEq for Job {
+            fn eq(&self, other: &Self) -> bool {
+                self.name == other.name
+            }
+        }
+
+        impl std::hash::Hash for Job {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.name.hash(state);
+            }
+        }
+
+        impl PartialOrd for Job {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                self.priority.partial_cmp(&other.priority)
+            }
+        }
+
+        let mut heap = BinaryHeap::<Job>::new(HeapKind::Max);
+        for (name, priority) in [("a", 9), ("b", 5), ("c", 7)] {
+            heap.insert(Job { name, priority }).unwrap();
+        }
+        heap.peek_mut().unwrap().priority = 1;
+        assert!(heap.validate());
+        assert_eq!(heap.peek().unwrap().name, "c");
+        let order: Vec<&str> = heap.into_sorted_vec().iter().map(|job| job.name).collect();
+        assert_eq!(order, vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_priority_queue_entry() {
+        fn schedule(
+            queue: &mut PriorityQueue<u32, &'static str>,
+            task: &'static str,
+            deadline: u32,
+        ) {
+            match queue.entry(task) {
+                Entry::Occupied(entry) => {
+                    if deadline < *entry.priority() {
+                        entry.update_priority(deadline);
+                    }
+                }
+                Entry::Vacant(entry) => entry.insert(deadline),
+            }
+        }
+
+        let mut queue = PriorityQueue::new(HeapKind::Min);
+        schedule(&mut queue, "backup", 30);
+        schedule(&mut queue, "report", 20);
+        assert!(matches!(queue.entry("backup"), Entry::Occupied(_)));
+        assert!(matches!(queue.entry("cleanup"), Entry::Vacant(_)));
+
+        schedule(&mut queue, "backup", 10);
+        schedule(&mut queue, "report", 25);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop(), Some((10, "backup")));
+        assert_eq!(queue.pop(), Some((20, "report")));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn test_empty_heap_skips_hashing_and_comparisons() {
+        use std::cell::Cell;
+        use std::collections::hash_map::DefaultHasher;
+
+        thread_local! {
+            static HASHES: Cell<usize> = const { Cell::new(0) };
+            static COMPARISONS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        #[derive(Default)]
+        struct CountingHasher;
+
+        impl std::hash::BuildHasher for CountingHasher {
+            type Hasher = DefaultHasher;
+
+            fn build_hasher(&self) -> DefaultHasher {
+                HASHES.with(|hashes| hashes.set(hashes.get() + 1));
+                DefaultHasher::new()
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Counted(i32);
+
+        impl PartialOrd for Counted {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                COMPARISONS.with(|comparisons| comparisons.set(comparisons.get() + 1));
+                self.0.partial_cmp(&other.0)
+            }
+        }
+
+        let mut heap = BinaryHeap::<Counted, CountingHasher>::new(HeapKind::Min);
+        assert_eq!(heap.extract_object(), None);
+        assert_eq!(heap.remove_object(&Counted(1)), None);
+        assert_eq!(heap.remove_object_last(&Counted(1)), None);
+        assert!(!heap.update(&Counted(1), |item| item.0 = 2));
+        assert_eq!(heap.peek(), None);
+        assert!(heap.peek_mut().is_none());
+        assert_eq!(heap.occurrences(&Counted(1)).count(), 0);
+        assert_eq!(HASHES.with(Cell::get), 0);
+        assert_eq!(COMPARISONS.with(Cell::get), 0);
+
+        heap.insert(Counted(1)).unwrap();
+        assert_eq!(heap.remove_object(&Counted(1)), Some(Counted(1)));
+        assert!(HASHES.with(Cell::get) > 0);
+    }
+
+    #[test]
+    fn test_merge_sorted_into() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[8, 2, 5, 11, 5]);
+        let mut out = vec![1, 5, 6, 9, 20];
+        heap.merge_sorted_into(&mut out);
+        assert_eq!(out, vec![1, 2, 5, 5, 5, 6, 8, 9, 11, 20]);
+
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[3, 7]);
+        let mut out = vec![];
+        heap.merge_sorted_into(&mut out);
+        assert_eq!(out, vec![7, 3]);
+    }
+
+    #[test]
+    fn test_extract_transaction() {
+        let tasks = [
+            Task {
+                priority: 2,
+                name: "a",
+            },
+            Task {
+                priority: 1,
+                name: "b",
+            },
+            Task {
+                priority: 2,
+                name: "c",
+            },
+            Task {
+                priority: 3,
+                name: "d",
+            },
+            Task {
+                priority: 2,
+                name: "e",
+            },
+        ];
+        let build = || {
+            let mut heap: BinaryHeap<Task> = BinaryHeapBuilder::new().stable(true).build();
+            tasks
+                .iter()
+                .for_each(|task| heap.insert(task.clone()).unwrap());
+            heap
+        };
+
+        let mut heap = build();
+        let rolled_back = heap.extract_transaction(3, |batch| {
+            assert_eq!(batch.len(), 3);
+            false
+        });
+        assert_eq!(rolled_back, None);
+        assert_eq!(heap.len(), 5);
+        assert_eq!(heap.into_sorted_vec(), build().into_sorted_vec());
+
+        let mut heap = build();
+        let committed = heap.extract_transaction(2, |_| true).unwrap();
+        let names: Vec<&str> = committed.iter().map(|task| task.name).collect();
+        assert_eq!(names, vec!["b", "a"]);
+        assert_eq!(heap.len(), 3);
+    }
+
+    #[test]
+    fn test_iter_range() {
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &(0..10).collect::<Vec<i32>>());
+        let in_range: HashSet<i32> = heap.iter_range(&3, &6).copied().collect();
+        assert_eq!(in_range, vec![3, 4, 5, 6].into_iter().collect());
+        assert_eq!(heap.iter_range(&20, &30).count(), 0);
+        assert_eq!(heap.len(), 10);
+
+        // The references outlive the bounds they were selected with.
+        fn between(heap: &BinaryHeap<i32>, lo: i32, hi: i32) -> Vec<&i32> {
+            heap.iter_range(&lo, &hi).collect()
+        }
+        assert_eq!(between(&heap, 8, 12), vec![&9, &8]);
+    }
+
+    #[test]
+    fn test_randomized_operations_stay_consistent() {
+        // insert, extract_object and remove_object check the heap and the index table after every
+        // call in debug builds, so any inconsistency panics right where it is introduced.
+        let mut rng = thread_rng();
+        for kind in [HeapKind::Min, HeapKind::Max] {
+            let mut heap = BinaryHeap::<i32>::new(kind);
+            for _ in 0..3000 {
+                match rng.gen_range(0..10) {
+                    0..=4 => heap.insert(rng.gen_range(0..40)).unwrap(),
+                    5..=6 => {
+                        heap.extract_object();
+                    }
+                    _ => {
+                        heap.remove_object(&rng.gen_range(0..40));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_counts() {
+        let heap = BinaryHeap::<i32>::from_counts(vec![(4, 3), (3, 1), (9, 0)], HeapKind::Min);
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.get_index(&4).unwrap().len(), 3);
+        heap.get_index(&4).unwrap().iter().for_each(|index| {
+            assert_eq!(heap.element_at(*index), Some(&4));
+        });
+        assert_eq!(heap.get_index(&3).unwrap().len(), 1);
+        assert!(heap.get_index(&9).is_none());
+        assert_eq!(heap.into_sorted_vec(), vec![3, 4, 4, 4]);
+    }
+
+    #[test]
+    fn test_peek_with_len() {
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        assert_eq!(heap.peek_with_len(), None);
+        insert_seed_data(&mut heap, &[3, 8, 1]);
+        assert_eq!(heap.peek_with_len(), Some((&8, 3)));
+    }
+
+    #[test]
+    fn test_rebuild_as() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[5, 2, 9, 2, 7, 1]);
+        assert!(heap.validate());
+
+        heap.rebuild_as(HeapKind::Max);
+        assert!(heap.is_max_heap());
+        assert!(heap.validate());
+        assert_eq!(heap.into_sorted_vec(), vec![9, 7, 5, 2, 2, 1]);
+    }
+
+    #[test]
+    fn test_into_sorted_boxed_slice() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[6, 2, 8, 2, 4]);
+        let sorted: Box<[i32]> = heap.into_sorted_boxed_slice();
+        assert_eq!(sorted.len(), 5);
+        assert_eq!(&sorted[..], &[2, 2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn test_reversed_view() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        assert_eq!(heap.reversed().peek(), None);
+        insert_seed_data(&mut heap, &[4, 11, 2, 7, 9, 2]);
+
+        let reversed = heap.reversed();
+        assert_eq!(reversed.peek(), Some(&11));
+        assert_eq!(reversed.len(), 6);
+        assert_eq!(
+            reversed.iter().copied().collect::<Vec<i32>>(),
+            vec![11, 9, 7, 4, 2, 2]
+        );
+        assert_eq!(heap.peek(), Some(&2));
+    }
+
+    #[test]
+    fn test_with_coalesce() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Equal (and hashed) by key, ordered by the accumulated weight.
+        #[derive(Debug, Clone, Eq)]
+        struct Weighted {
+            key: &'static str,
+            weight: u32,
+        }
+
+        impl PartialEq for Weighted {
+            fn eq(&self, other: &Self) -> bool {
+                self.key == other.key
+            }
+        }
+
+        impl std::hash::Hash for Weighted {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.key.hash(state);
+            }
+        }
+
+        impl PartialOrd for Weighted {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                self.weight.partial_cmp(&other.weight)
+            }
+        }
+
+        let weighted = |key, weight| Weighted { key, weight };
+        let merges = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&merges);
+        let mut heap =
+            BinaryHeap::<Weighted>::with_coalesce(HeapKind::Max, move |stored, inserted| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                stored.weight += inserted.weight
+            });
+        heap.insert(weighted("a", 2)).unwrap();
+        heap.insert(weighted("b", 3)).unwrap();
+        heap.insert(weighted("c", 4)).unwrap();
+        assert_eq!(heap.peek().unwrap().key, "c");
+
+        heap.insert(weighted("b", 2)).unwrap();
+        heap.insert(weighted("b", 1)).unwrap();
+        heap.insert(weighted("c", 1)).unwrap();
+        assert_eq!(heap.len(), 3);
+        assert_eq!(merges.load(Ordering::SeqCst), 3);
+        assert_eq!(heap.occurrences(&weighted("c", 0)).count(), 1);
+        assert!(heap.validate());
+
+        let drained: Vec<(&str, u32)> = heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|item| (item.key, item.weight))
+            .collect();
+        assert_eq!(drained, vec![("b", 6), ("c", 5), ("a", 2)]);
+    }
+
+    #[test]
+    fn test_remove_object_prefers_tail_occurrence() {
+        // Only occurrence at the tail, at the root and in the middle.
+        for (data, target) in [
+            (&[1, 2, 3][..], 3),
+            (&[1, 2, 3][..], 1),
+            (&[1, 5, 2, 6, 7][..], 5),
+        ] {
+            let mut heap = BinaryHeap::new(HeapKind::Min);
+            insert_seed_data(&mut heap, data);
+            assert_eq!(heap.remove_object(&target), Some(target));
+            assert!(heap.get_index(&target).is_none());
+            assert!(heap.validate());
+        }
+
+        // With duplicates the copy at the tail goes first, leaving the rest of the heap in place.
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[1, 4, 2, 6, 5, 3, 4]);
+        assert_eq!(heap.get_index(&4).map(<[usize]>::len), Some(2));
+        let version_before = heap.version();
+        assert_eq!(heap.element_at(heap.len() - 1), Some(&4));
+        assert_eq!(heap.remove_object(&4), Some(4));
+        assert_eq!(heap.version(), version_before + 1);
+        assert_eq!(heap.element_at(1), Some(&4));
+        assert!(heap.validate());
+        assert_eq!(heap.into_sorted_vec(), vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_try_from_vec() {
+        use std::convert::TryFrom;
+
+        let heap = BinaryHeap::<i32>::try_from((vec![1, 3, 2, 7, 4, 5], HeapKind::Min)).unwrap();
+        assert_eq!(heap.len(), 6);
+        assert_eq!(heap.element_at(3), Some(&7));
+        assert!(heap.validate());
+        assert_eq!(heap.into_sorted_vec(), vec![1, 2, 3, 4, 5, 7]);
+
+        // 3 at index 1 has a smaller child at index 4.
+        let invalid = BinaryHeap::<i32>::try_from((vec![1, 3, 2, 7, 0], HeapKind::Min));
+        assert_eq!(
+            invalid.err(),
+            Some(HeapViolation {
+                index: 1,
+                elements: vec![1, 3, 2, 7, 0],
+            })
+        );
+        assert!(BinaryHeap::<i32>::try_from((vec![], HeapKind::Max)).is_ok());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &(0..100).collect::<Vec<i32>>());
+        assert_eq!(heap.histogram(10, &0, &100), vec![10; 10]);
+        assert_eq!(heap.histogram(10, &0, &99).iter().sum::<usize>(), 100);
+
+        insert_seed_data(&mut heap, &[-5, 150]);
+        assert_eq!(heap.histogram(4, &0, &99), vec![25, 25, 25, 25]);
+        assert_eq!(heap.histogram(0, &0, &99), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_extract_object_small_heaps() {
+        for kind in [HeapKind::Min, HeapKind::Max] {
+            let mut heap = BinaryHeap::new(kind);
+            heap.insert(4).unwrap();
+            assert_eq!(heap.extract_object(), Some(4));
+            assert!(heap.get_index(&4).is_none());
+            assert_eq!(heap.extract_object(), None);
+
+            for data in [[3, 8], [8, 3], [5, 5]] {
+                insert_seed_data(&mut heap, &data);
+                let (first, second) = match kind {
+                    HeapKind::Min => (data[0].min(data[1]), data[0].max(data[1])),
+                    HeapKind::Max => (data[0].max(data[1]), data[0].min(data[1])),
+                };
+                assert_eq!(heap.extract_object(), Some(first));
+                assert_eq!(heap.peek(), Some(&second));
+                assert_eq!(heap.get_index(&second), Some(&[0][..]));
+                assert!(heap.validate());
+                assert_eq!(heap.extract_object(), Some(second));
+                assert!(heap.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_in_place() {
+        let data = [4, -2, 9, 0, 4, 7];
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &data);
+        heap.map_in_place(|item| *item = (*item - 3).abs());
+        assert!(heap.validate());
+
+        let mut fresh = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(
+            &mut fresh,
+            &data
+                .iter()
+                .map(|item| (item - 3).abs())
+                .collect::<Vec<i32>>(),
+        );
+        assert_eq!(heap.into_sorted_vec(), fresh.into_sorted_vec());
+    }
+
+    #[test]
+    fn test_root_index() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        assert_eq!(heap.root_index(), None);
+        insert_seed_data(&mut heap, &[5, 3]);
+        assert_eq!(heap.root_index(), Some(0));
+        assert_eq!(heap.element_at(heap.root_index().unwrap()), heap.peek());
+    }
+
+    #[test]
+    fn test_drain_sorted_rev() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[6, 1, 9, 4, 9, 2, 7]);
+        assert_eq!(
+            heap.drain_sorted_rev().take(3).collect::<Vec<i32>>(),
+            vec![9, 9, 7]
+        );
+        assert!(heap.validate());
+        assert_eq!(heap.len(), 4);
+
+        assert_eq!(
+            heap.drain_sorted_rev().collect::<Vec<i32>>(),
+            vec![6, 4, 2, 1]
+        );
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_policy() {
+        let data = [3, 1, 3, 2, 1, 3];
+
+        let mut heap =
+            BinaryHeap::<i32>::with_duplicate_policy(HeapKind::Min, DuplicatePolicy::Allow);
+        data.iter().for_each(|item| heap.insert(*item).unwrap());
+        assert_eq!(heap.len(), 6);
+
+        let mut heap =
+            BinaryHeap::<i32>::with_duplicate_policy(HeapKind::Min, DuplicatePolicy::Forbid);
+        data.iter().for_each(|item| heap.insert(*item).unwrap());
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.checked_insert(2), InsertResult::Rejected(2));
+        assert_eq!(heap.checked_insert(4), InsertResult::Accepted);
+        heap.extend(vec![4, 5, 5]);
+        assert_eq!(heap.into_sorted_vec(), vec![1, 2, 3, 4, 5]);
+
+        // Equal values merge into one, here into their running total.
+        let mut heap = BinaryHeap::<i32>::with_duplicate_policy(
+            HeapKind::Max,
+            DuplicatePolicy::coalesce(|stored, inserted| *stored += inserted),
+        );
+        data.iter().for_each(|item| heap.insert(*item).unwrap());
+        // 3 + 3 = 6 and 1 + 1 = 2, after which the last 3 has no equal left to merge into. Merging
+        // may produce a duplicate (the two 2s), only inserting one is prevented.
+        assert_eq!(heap.len(), 4);
+        assert!(heap.validate());
+        assert_eq!(heap.into_sorted_vec(), vec![6, 3, 2, 2]);
+
+        // Different values sharing a hash are not duplicates of each other.
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+        struct Colliding(i32);
+
+        impl std::hash::Hash for Colliding {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                0.hash(state);
+            }
+        }
+
+        let mut heap =
+            BinaryHeap::<Colliding>::with_duplicate_policy(HeapKind::Min, DuplicatePolicy::Forbid);
+        [1, 2, 1, 3]
+            .iter()
+            .for_each(|item| heap.insert(Colliding(*item)).unwrap());
+        assert_eq!(heap.len(), 3);
+    }
+
+    #[test]
+    fn test_raw_swap_remove() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &(0..20).collect::<Vec<i32>>());
+        assert_eq!(heap.raw_swap_remove(20), None);
+
+        let mut removed = vec![];
+        for index in [0, 3, 0, 7] {
+            removed.push(heap.raw_swap_remove(index).unwrap());
+        }
+        assert_eq!(heap.len(), 16);
+        // The table is kept up to date even while the heap property isn't.
+        removed
+            .iter()
+            .for_each(|item| assert!(heap.get_index(item).is_none()));
+
+        heap.rebuild_as(HeapKind::Min);
+        assert!(heap.validate());
+        let mut expected: Vec<i32> = (0..20).filter(|item| !removed.contains(item)).collect();
+        expected.sort_unstable();
+        assert_eq!(heap.into_sorted_vec(), expected);
+    }
+
+    #[test]
+    fn test_compare() {
+        let min_heap = BinaryHeap::<i32>::new(HeapKind::Min);
+        assert!(min_heap.compare(&1, &5));
+        assert!(!min_heap.compare(&5, &1));
+        assert!(min_heap.compare(&3, &3));
+
+        let max_heap = BinaryHeap::<i32>::new(HeapKind::Max);
+        assert!(max_heap.compare(&5, &1));
+        assert!(!max_heap.compare(&1, &5));
+        assert!(max_heap.compare(&3, &3));
+    }
+
+    #[test]
+    fn test_merge_with_sorted() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[10, 4, 7, 1]);
+        let sorted = [2, 4, 8, 12];
+        assert_eq!(
+            heap.merge_with_sorted(&sorted),
+            vec![1, 2, 4, 4, 7, 8, 10, 12]
+        );
+    }
+
+    #[test]
+    fn test_hard_capacity() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        heap.set_hard_capacity(3);
+        insert_seed_data(&mut heap, &[5, 2, 8]);
+
+        assert_eq!(heap.insert(1), Err(CapacityExceeded(1)));
+        assert_eq!(heap.checked_insert(1), InsertResult::Rejected(1));
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.peek(), Some(&2));
+
+        heap.extract_object();
+        assert_eq!(heap.insert(1), Ok(()));
+        assert_eq!(heap.insert_all_unique(vec![9]), Err(CapacityExceeded(9)));
+        assert_eq!(heap.into_sorted_vec(), vec![1, 5, 8]);
+    }
+
+    #[test]
+    #[should_panic(expected = "hard capacity of 2 elements exceeded")]
+    fn test_extend_past_hard_capacity_panics() {
+        let mut heap: BinaryHeap<i32> = BinaryHeap::new(HeapKind::Max);
+        heap.set_hard_capacity(2);
+        heap.extend(vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_try_extend() {
+        let mut heap: BinaryHeap<i32> = BinaryHeap::new(HeapKind::Max);
+        assert_eq!(heap.try_extend(vec![4, 1]), Ok(()));
+        heap.set_hard_capacity(4);
+        assert_eq!(
+            heap.try_extend(vec![6, 2, 9, 3]),
+            Err(CapacityExceeded(vec![9, 3]))
+        );
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.try_extend(vec![]), Ok(()));
+        assert_eq!(heap.into_sorted_vec(), vec![6, 4, 2, 1]);
+    }
+
+    #[test]
+    fn test_shed_to() {
+        let mut data: Vec<i32> = (0..20).collect();
+        data.shuffle(&mut thread_rng());
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &data);
+
+        assert_eq!(heap.shed_to(5), (5..20).collect::<Vec<i32>>());
+        assert!(heap.validate());
+        assert_eq!(heap.shed_to(5), Vec::<i32>::new());
+        assert_eq!(heap.into_sorted_vec(), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_heaps_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<BinaryHeap<i32>>();
+        assert_send_sync::<SyncHeap<i32>>();
+    }
+
+    #[test]
+    fn test_sync_heap_producers_and_consumer() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        use std::thread;
+
+        const PRODUCERS: u64 = 4;
+        const PER_PRODUCER: u64 = 500;
+        let heap: Arc<SyncHeap<u64>> = Arc::new(SyncHeap::new(HeapKind::Max));
+        let done = Arc::new(AtomicBool::new(false));
+
+        let consumer = {
+            let heap = Arc::clone(&heap);
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                let mut during = Vec::new();
+                while !done.load(Ordering::SeqCst) {
+                    if let Some(item) = heap.try_pop() {
+                        during.push(item);
+                    }
+                }
+                // Nothing is pushed any more, so the rest must come out in priority order.
+                let mut after = Vec::new();
+                while let Some(item) = heap.pop() {
+                    after.push(item);
+                }
+                (during, after)
+            })
+        };
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let heap = Arc::clone(&heap);
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        heap.push(i * PRODUCERS + p).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        done.store(true, Ordering::SeqCst);
+
+        let (during, after) = consumer.join().unwrap();
+        assert!(after.windows(2).all(|pair| pair[0] > pair[1]));
+        let mut consumed: Vec<u64> = during.into_iter().chain(after).collect();
+        consumed.sort_unstable();
+        assert_eq!(
+            consumed,
+            (0..PRODUCERS * PER_PRODUCER).collect::<Vec<u64>>()
+        );
+        assert!(heap.is_empty());
+        assert_eq!(heap.peek_cloned(), None);
+    }
+
+    #[test]
+    fn test_sync_heap_peek_cloned() {
+        let heap: SyncHeap<i32> = SyncHeap::new(HeapKind::Min);
+        heap.push(3).unwrap();
+        heap.push(1).unwrap();
+        heap.push(2).unwrap();
+        assert_eq!(heap.peek_cloned(), Some(1));
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.pop(), Some(1));
+        assert_eq!(heap.into_inner().into_sorted_vec(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_sync_heap_recovers_from_panic() {
+        let coalescing = BinaryHeap::<i32>::with_coalesce(HeapKind::Min, |stored, inserted| {
+            *stored += inserted * 10;
+            panic!("merge failed");
+        });
+        let heap = SyncHeap::from_heap(coalescing);
+        [4, 2, 6].iter().for_each(|item| heap.push(*item).unwrap());
+
+        // The merge panics with the lock held, after turning the root into 22 but before it could
+        // be sifted down or put back into the index table.
+        let pushed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| heap.push(2)));
+        assert!(pushed.is_err());
+
+        assert_eq!(heap.pop(), Some(4));
+        heap.push(1).unwrap();
+        let heap = heap.into_inner();
+        assert!(heap.validate());
+        assert_eq!(heap.into_sorted_vec(), vec![1, 6, 22]);
+    }
+
+    #[test]
+    fn test_on_root_change() {
+        use std::sync::{Arc, Mutex};
+
+        let roots = Arc::new(Mutex::new(vec![]));
+        let seen = Arc::clone(&roots);
+        let mut heap: BinaryHeap<i32> = BinaryHeap::new(HeapKind::Min);
+        heap.set_on_root_change(move |root| seen.lock().unwrap().push(*root));
+        let reported = || roots.lock().unwrap().clone();
+
+        heap.insert(10).unwrap();
+        assert_eq!(reported(), vec![10]);
+        heap.insert(20).unwrap();
+        heap.insert(15).unwrap();
+        assert_eq!(reported(), vec![10]);
+        heap.insert(5).unwrap();
+        assert_eq!(reported(), vec![10, 5]);
+        heap.remove_object(&20);
+        heap.remove_object(&42);
+        assert_eq!(reported(), vec![10, 5]);
+        heap.remove_object(&5);
+        assert_eq!(reported(), vec![10, 5, 10]);
+        assert_eq!(heap.extract_object(), Some(10));
+        assert_eq!(reported(), vec![10, 5, 10, 15]);
+        // The heap becoming empty has no root to report.
+        assert_eq!(heap.extract_object(), Some(15));
+        assert_eq!(heap.extract_object(), None);
+        assert_eq!(reported().len(), 4);
+
+        roots.lock().unwrap().clear();
+        heap.extend(vec![8, 3, 9, 6]);
+        assert_eq!(reported(), vec![3]);
+        assert_eq!(heap.remove_first_matching(|item| *item == 8), Some(8));
+        assert_eq!(heap.remove_first_matching(|item| *item == 3), Some(3));
+        assert_eq!(reported(), vec![3, 6]);
+        heap.truncate(1);
+        assert_eq!(reported(), vec![3, 6]);
+        assert_eq!(heap.extract_transaction(1, |_| false), None);
+        assert_eq!(reported(), vec![3, 6]);
+
+        // The split off part reports to the same callback.
+        heap.insert(9).unwrap();
+        let mut worse = heap.split_off(&6);
+        worse.insert(7).unwrap();
+        assert_eq!(reported(), vec![3, 6, 7]);
+    }
+
+    #[test]
+    fn test_on_root_change_after_rollback() {
+        use std::sync::{Arc, Mutex};
+
+        // Equal (and hashed) by name, ordered by priority.
+        #[derive(Debug, Clone, Eq)]
+        struct Job {
+            name: &'static str,
+            priority: u32,
+        }
+
+        impl PartialEq for Job {
+            fn eq(&self, other: &Self) -> bool {
+                self.name == other.name
+            }
+        }
+
+        impl std::hash::Hash for Job {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.name.hash(state);
+            }
+        }
+
+        impl PartialOrd for Job {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                self.priority.partial_cmp(&other.priority)
+            }
+        }
+
+        let roots = Arc::new(Mutex::new(vec![]));
+        let seen = Arc::clone(&roots);
+        let mut heap = BinaryHeap::<Job>::new(HeapKind::Max);
+        for name in ["a", "b", "c", "d"] {
+            heap.insert(Job { name, priority: 1 }).unwrap();
+        }
+        heap.set_on_root_change(move |root: &Job| seen.lock().unwrap().push(root.name));
+
+        assert_eq!(heap.extract_transaction(2, |_| false), None);
+        assert_eq!(heap.len(), 4);
+        // Putting a and c back leaves c, of the same priority, on top in place of a.
+        assert_eq!(heap.peek().unwrap().name, "c");
+        assert_eq!(*roots.lock().unwrap(), vec!["c"]);
+    }
+
+    #[test]
+    fn test_on_root_change_in_place() {
+        use std::sync::{Arc, Mutex};
+
+        let roots = Arc::new(Mutex::new(vec![]));
+        let seen = Arc::clone(&roots);
+        let mut heap: BinaryHeap<i32> =
+            BinaryHeap::with_coalesce(HeapKind::Max, |stored, inserted| *stored += inserted);
+        heap.set_on_root_change(move |root| seen.lock().unwrap().push(*root));
+        let reported = || roots.lock().unwrap().clone();
+        heap.extend(vec![5, 3, 1]);
+        roots.lock().unwrap().clear();
+
+        // Changes below the root that leave it on top aren't reported.
+        assert!(heap.update(&1, |item| *item = 2));
+        assert!(reported().is_empty());
+        assert!(heap.update(&3, |item| *item = 7));
+        assert_eq!(reported(), vec![7]);
+        // The root keeps its place but not its value.
+        assert!(heap.update(&7, |item| *item = 8));
+        assert_eq!(reported(), vec![7, 8]);
+        heap.insert(8).unwrap();
+        assert_eq!(reported(), vec![7, 8, 16]);
+        *heap.peek_mut().unwrap() = 1;
+        assert_eq!(reported(), vec![7, 8, 16, 5]);
+        assert_eq!(heap.into_sorted_vec(), vec![5, 2, 1]);
+    }
+
+    #[test]
+    fn test_peek_clone() {
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        assert_eq!(heap.peek_clone(), None);
+        insert_seed_data(&mut heap, &[4, 9, 1, 7]);
+
+        let root = heap.peek_clone();
+        assert_eq!(root.as_ref(), heap.peek());
+        assert_eq!(root, Some(9));
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.into_sorted_vec(), vec![9, 7, 4, 1]);
+    }
+
+    #[test]
+    fn test_indices_of() {
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[3, 8, 3, 1, 3]);
+        assert_eq!(heap.indices_of(&42), Vec::<usize>::new());
+
+        let indices = heap.indices_of(&3);
+        assert!(indices
+            .iter()
+            .all(|&index| heap.element_at(index) == Some(&3)));
+        // The heap can be mutated while the positions are still in use.
+        heap.remove_object(&3);
+        heap.insert(5).unwrap();
+        assert_eq!(heap.indices_of(&3).len(), indices.len() - 1);
+    }
+
+    #[test]
+    fn test_fold_sorted() {
+        let mut data: Vec<i32> = (0..50).collect();
+        data.shuffle(&mut thread_rng());
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &data);
+        let collected = heap.fold_sorted(Vec::new(), |mut acc, item| {
+            acc.push(item);
+            acc
+        });
+        assert_eq!(collected, (0..50).collect::<Vec<i32>>());
+
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[1, 2, 3, 4]);
+        // Weighted by extraction position: 4 * 1 + 3 * 2 + 2 * 3 + 1 * 4.
+        let (weighted, _) = heap.fold_sorted((0, 1), |(sum, weight), item| {
+            (sum + item * weight, weight + 1)
+        });
+        assert_eq!(weighted, 20);
+        assert_eq!(
+            BinaryHeap::<i32>::new(HeapKind::Max).fold_sorted(0, |a, b| a + b),
+            0
+        );
+    }
+
+    #[test]
+    fn test_new_with_min_cap() {
+        let mut heap = BinaryHeap::<i32>::new_with_min_cap(HeapKind::Max);
+        let initial = heap.capacity();
+        assert!(initial >= DEFAULT_MIN_CAPACITY);
+        (0..DEFAULT_MIN_CAPACITY as i32).for_each(|item| heap.insert(item).unwrap());
+        assert_eq!(heap.capacity(), initial);
+        assert_eq!(BinaryHeap::<i32>::new(HeapKind::Max).capacity(), 0);
+    }
+
+    #[test]
+    fn test_relax() {
+        // A node keyed by id alone, ordered by its tentative distance.
+        #[derive(Debug, Clone, Copy)]
+        struct Node {
+            id: u32,
+            dist: u32,
+        }
+        impl PartialEq for Node {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
+        }
+        impl Eq for Node {}
+        impl std::hash::Hash for Node {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.id.hash(state);
+            }
+        }
+        impl PartialOrd for Node {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                self.dist.partial_cmp(&other.dist)
+            }
+        }
+
+        let mut frontier: BinaryHeap<Node> = BinaryHeap::new(HeapKind::Min);
+        let edges = [
+            (1, 7),
+            (2, 9),
+            (3, 14),
+            (2, 10),
+            (3, 11),
+            (1, 7),
+            (3, 20),
+            (2, 8),
+        ];
+        let improved: Vec<bool> = edges
+            .iter()
+            .map(|&(id, dist)| frontier.relax(Node { id, dist }))
+            .collect();
+        assert_eq!(
+            improved,
+            vec![true, true, true, false, true, true, false, true]
+        );
+        assert_eq!(frontier.len(), 3);
+        let settled: Vec<(u32, u32)> = frontier
+            .into_sorted_vec()
+            .into_iter()
+            .map(|node| (node.id, node.dist))
+            .collect();
+        assert_eq!(settled, vec![(1, 7), (2, 8), (3, 11)]);
+    }
+
+    #[test]
+    fn test_extract_extremes() {
+        let mut data: Vec<i32> = (0..10).collect();
+        data.shuffle(&mut thread_rng());
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &data);
+
+        assert_eq!(heap.extract_extremes(), Some((0, 9)));
+        assert_eq!(heap.len(), 8);
+        assert_eq!(heap.extract_extremes(), Some((1, 8)));
+        assert!(heap.validate());
+
+        let mut single: BinaryHeap<i32> = BinaryHeap::new(HeapKind::Min);
+        assert_eq!(single.extract_extremes(), None);
+        single.insert(3).unwrap();
+        assert_eq!(single.extract_extremes(), None);
+        assert_eq!(single.len(), 1);
+        single.insert(1).unwrap();
+        assert_eq!(single.extract_extremes(), Some((1, 3)));
+        assert!(single.is_empty());
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[1, 2, 3, 4, 5, 6]);
+        // Inserted in order, so the tree is 1 / (2, 3) / (4, 5), (6).
+        let mut cursor = heap.cursor();
+        assert_eq!(cursor.value(), Some(&1));
+        assert!(!cursor.up());
+
+        assert!(cursor.right());
+        assert_eq!(cursor.value(), Some(&3));
+        assert!(!cursor.right());
+        assert!(cursor.left());
+        assert_eq!((cursor.index(), cursor.value()), (5, Some(&6)));
+        assert!(!cursor.left());
+        assert_eq!(cursor.value(), Some(&6));
+
+        assert!(cursor.up());
+        assert!(cursor.up());
+        assert_eq!(cursor.value(), Some(&1));
+        assert!(cursor.left());
+        assert!(cursor.right());
+        assert_eq!(cursor.value(), Some(&5));
+
+        let empty: BinaryHeap<i32> = BinaryHeap::new(HeapKind::Min);
+        let mut cursor = empty.cursor();
+        assert_eq!(cursor.value(), None);
+        assert!(!cursor.left() && !cursor.right() && !cursor.up());
+    }
+
+    #[test]
+    fn test_insert_all_unique() {
+        let mut heap: BinaryHeap<i32> = BinaryHeap::new(HeapKind::Min);
+        heap.insert_all_unique(vec![4, 4, 3, 3, 5]).unwrap();
+        assert_eq!(heap.len(), 3);
+        assert!(heap.validate());
+
+        heap.insert_all_unique(vec![5, 1, 1, 4]).unwrap();
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.indices_of(&1).len(), 1);
+        assert_eq!(heap.into_sorted_vec(), vec![1, 3, 4, 5]);
+
+        // The values go in in input order, so which ones fit under a hard capacity is fixed.
+        let mut heap: BinaryHeap<i32> = BinaryHeap::new(HeapKind::Min);
+        heap.set_hard_capacity(5);
+        let items = vec![9, 2, 9, 7, 0, 2, 5, 8, 1];
+        assert_eq!(heap.insert_all_unique(items), Err(CapacityExceeded(8)));
+        assert_eq!(heap.into_sorted_vec(), vec![0, 2, 5, 7, 9]);
+    }
+
+    #[test]
+    fn test_kth() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[42, 7, 19, 3, 88, 11, 7]);
+        assert_eq!(heap.kth(0), heap.peek());
+        assert_eq!(heap.kth(2), Some(&7));
+        assert_eq!(heap.kth(3), Some(&11));
+        assert_eq!(heap.kth(6), Some(&88));
+        assert_eq!(heap.kth(7), None);
+        assert_eq!(heap.len(), 7);
+    }
+
+    #[test]
+    fn test_swap_values() {
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[10, 9, 8, 7, 6, 5, 4, 3]);
+        // An ancestor and its descendant, then two unrelated positions.
+        assert!(heap.swap_values(&10, &3));
+        assert!(heap.validate());
+        assert!(heap.swap_values(&9, &4));
+        assert!(heap.validate());
+        assert!(!heap.swap_values(&9, &42));
+        assert_eq!(heap.len(), 8);
+
+        let mut rng = thread_rng();
+        let data: Vec<i32> = (0..200).map(|_| rng.gen_range(0..50)).collect();
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &data);
+        for _ in 0..200 {
+            let (a, b) = (
+                *data.choose(&mut rng).unwrap(),
+                *data.choose(&mut rng).unwrap(),
+            );
+            assert!(heap.swap_values(&a, &b));
+            assert!(heap.validate());
+        }
+        let mut sorted = data;
+        sorted.sort_unstable();
+        assert_eq!(heap.into_sorted_vec(), sorted);
+    }
+
+    #[test]
+    fn test_memory_footprint() {
+        let empty = BinaryHeap::<u64>::new(HeapKind::Max).memory_footprint();
+        assert_eq!(empty.total(), 0);
+
+        let small = BinaryHeap::<u64>::new_with_capacity(HeapKind::Max, 100).memory_footprint();
+        let large = BinaryHeap::<u64>::new_with_capacity(HeapKind::Max, 1000).memory_footprint();
+        assert!(small.elements >= 100 * 8);
+        assert!(large.elements >= 1000 * 8);
+        assert!(large.elements > small.elements * 5);
+        assert!(large.index_table > small.index_table);
+
+        let mut heap = BinaryHeap::<u64>::new(HeapKind::Max);
+        (0..50).for_each(|item| heap.insert(item).unwrap());
+        let report = heap.memory_footprint();
+        assert_eq!(report.elements, heap.capacity() * 8);
+        // Each of the 50 distinct values has its own bucket and index list.
+        assert!(report.index_table >= 50 * (8 + std::mem::size_of::<Vec<usize>>() + 8));
+    }
+
+    #[test]
+    fn test_debug_dump() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[5, 3, 8, 1, 4, 7]);
+        let expected = "\
+level 0
+  0: 1 parent: - children: [3, 7]
+level 1
+  1: 3 parent: 1 children: [5, 4]
+  2: 7 parent: 1 children: [8]
+level 2
+  3: 5 parent: 3 children: []
+  4: 4 parent: 3 children: []
+  5: 8 parent: 7 children: []
+";
+        assert_eq!(heap.debug_dump(), expected);
+        assert_eq!(BinaryHeap::<i32>::new(HeapKind::Min).debug_dump(), "");
+    }
+
+    #[test]
+    fn test_change_priorities() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[10, 20, 30, 40, 50, 20]);
+        heap.change_priorities(vec![(50, 5), (20, 35), (20, 45), (99, 1), (30, 25)]);
+        assert!(heap.validate());
+        assert_eq!(heap.into_sorted_vec(), vec![5, 10, 25, 35, 40, 45]);
+
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[1, 2, 3]);
+        // The second update looks for a 4 that only the first one produced.
+        heap.change_priorities(vec![(1, 4), (4, 9)]);
+        assert_eq!(heap.into_sorted_vec(), vec![4, 3, 2]);
+    }
+
+    #[test]
+    fn test_top_k_into() {
+        let mut data: Vec<i32> = (0..20).collect();
+        data.shuffle(&mut thread_rng());
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &data);
+
+        let mut out = [0; 5];
+        assert_eq!(heap.top_k_into(&mut out), 5);
+        assert_eq!(out, [19, 18, 17, 16, 15]);
+        assert_eq!(heap.len(), 20);
+
+        let mut small = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut small, &[2, 7]);
+        let mut out = [-1; 5];
+        assert_eq!(small.top_k_into(&mut out), 2);
+        assert_eq!(out, [7, 2, -1, -1, -1]);
+
+        // Copies of the same value each take a slot.
+        insert_seed_data(&mut small, &[7, 5]);
+        let mut out = [0; 3];
+        assert_eq!(small.top_k_into(&mut out), 3);
+        assert_eq!(out, [7, 7, 5]);
+    }
+
+    #[test]
+    fn test_has_duplicates() {
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[1, 2, 3]);
+        assert!(!heap.has_duplicates());
+
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &[1, 2, 2]);
+        assert!(heap.has_duplicates());
+        heap.remove_object(&2);
+        assert!(!heap.has_duplicates());
+        // Removing the last copy leaves an empty bucket behind.
+        heap.remove_object(&2);
+        heap.insert(1).unwrap();
+        assert!(heap.has_duplicates());
+    }
+
+    #[test]
+    fn test_reentrant_drop_of_extracted_element() {
+        use std::cell::RefCell;
+
+        // Dropping an odd job schedules its (even) follow up on the same heap.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd)]
+        struct Job(u32);
+        impl Drop for Job {
+            fn drop(&mut self) {
+                if self.0 % 2 == 1 {
+                    let follow_up = Job(self.0 - 1);
+                    QUEUE
+                        .with(|queue| queue.borrow_mut().insert(follow_up))
+                        .unwrap();
+                }
+            }
+        }
+        thread_local! {
+            static QUEUE: RefCell<BinaryHeap<Job>> = RefCell::new(BinaryHeap::new(HeapKind::Max));
+        }
+
+        QUEUE.with(|queue| (1..=9).for_each(|n| queue.borrow_mut().insert(Job(n)).unwrap()));
+        let mut processed = vec![];
+        while let Some(job) = QUEUE.with(|queue| queue.borrow_mut().extract_object()) {
+            processed.push(job.0);
+            drop(job);
+            assert!(QUEUE.with(|queue| queue.borrow().validate()));
+        }
+        assert_eq!(processed, vec![9, 8, 8, 7, 6, 6, 5, 4, 4, 3, 2, 2, 1, 0]);
+        assert!(QUEUE.with(|queue| queue.borrow().is_empty()));
+    }
+
+    #[test]
+    fn test_top_k_from() {
+        let mut top = BinaryHeap::<i32>::top_k_from(0..1000, 5, HeapKind::Max);
+        assert_eq!(top.len(), 5);
+        assert!(top.validate());
+        assert_eq!(top.extract_object(), Some(999));
+        assert_eq!(top.into_sorted_vec(), vec![998, 997, 996, 995]);
+
+        let mut data: Vec<i32> = (0..100).collect();
+        data.shuffle(&mut thread_rng());
+        let bottom = BinaryHeap::<i32>::top_k_from(data, 3, HeapKind::Min);
+        assert_eq!(bottom.into_sorted_vec(), vec![0, 1, 2]);
+        assert!(BinaryHeap::<i32>::top_k_from(0..10, 0, HeapKind::Max).is_empty());
+    }
+
+    #[test]
+    fn test_elements_mut() {
+        // Extracting from two elements pops the front of the storage, so the elements inserted
+        // afterwards wrap around its buffer, which the guard has to make contiguous.
+        let mut heap = BinaryHeap::new_with_capacity(HeapKind::Max, 30);
+        insert_seed_data(&mut heap, &[50, 40]);
+        heap.extract_object();
+        heap.extract_object();
+        let mut data: Vec<i32> = (0..30).collect();
+        data.shuffle(&mut thread_rng());
+        insert_seed_data(&mut heap, &data);
+
+        {
+            let mut elements = heap.elements_mut();
+            assert_eq!(elements.len(), 30);
+            elements.sort_unstable();
+            assert_eq!(elements[0], 0);
+            elements[29] = 100;
+        }
+        assert!(heap.validate());
+        assert_eq!(heap.extract_object(), Some(100));
+        assert_eq!(heap.indices_of(&29).len(), 0);
+        assert_eq!(heap.into_sorted_vec(), (0..29).rev().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_remove_object_all() {
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[7, 3, 7, 9, 7, 1, 7, 5]);
+        assert_eq!(heap.remove_object_all(&7), vec![7, 7, 7, 7]);
+        assert_eq!(heap.len(), 4);
+        assert!(heap.get_index(&7).is_none());
+        assert!(heap.validate());
+        assert_eq!(heap.remove_object_all(&7), Vec::<i32>::new());
+        assert_eq!(heap.into_sorted_vec(), vec![9, 5, 3, 1]);
+    }
+
+    #[test]
+    fn test_cmp_to_root() {
+        use std::cmp::Ordering;
+        let mut heap = BinaryHeap::new(HeapKind::Min);
+        assert_eq!(heap.cmp_to_root(&1), None);
+        insert_seed_data(&mut heap, &[5, 8, 6]);
+        assert_eq!(heap.cmp_to_root(&2), Some(Ordering::Less));
+        assert_eq!(heap.cmp_to_root(&5), Some(Ordering::Equal));
+        assert_eq!(heap.cmp_to_root(&7), Some(Ordering::Greater));
+
+        let mut heap = BinaryHeap::new(HeapKind::Max);
+        insert_seed_data(&mut heap, &[5, 8, 6]);
+        assert_eq!(heap.cmp_to_root(&9), Some(Ordering::Less));
+        assert_eq!(heap.cmp_to_root(&2), Some(Ordering::Greater));
+    }
+
+    #[test]
+    fn test_split_off_keeps_total_order() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Reading(i32);
+
+        #[allow(clippy::non_canonical_partial_ord_impl)]
+        impl PartialOrd for Reading {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                if self.0 < 0 || other.0 < 0 {
+                    None
+                } else {
+                    Some(self.0.cmp(&other.0))
+                }
+            }
+        }
+
+        impl Ord for Reading {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let mut heap = BinaryHeap::<Reading>::new_ord(HeapKind::Max);
+        (-10..10).for_each(|item| heap.insert(Reading(item)).unwrap());
+        let mut rest = heap.split_off(&Reading(0));
+        [-20, -15, -11]
+            .iter()
+            .for_each(|item| rest.insert(Reading(*item)).unwrap());
+
+        let sorted: Vec<i32> = rest.into_sorted_vec().into_iter().map(|r| r.0).collect();
+        let mut expected: Vec<i32> = (-10..0).chain(vec![-20, -15, -11]).collect();
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(sorted, expected);
+        assert_eq!(heap.len(), 10);
+    }
+
+    #[test]
+    fn test_notify_swapped() {
+        let mut heap: BinaryHeap<i32> = BinaryHeap::new(HeapKind::Min);
+        insert_seed_data(&mut heap, &(0..8).collect::<Vec<i32>>());
+
+        let swaps = [(0, 7), (2, 5), (7, 3), (4, 4), (1, 6)];
+        let storage = heap.raw_elements_mut();
+        swaps
+            .iter()
+            .for_each(|(ind1, ind2)| storage.swap(*ind1, *ind2));
+        heap.notify_swapped(&swaps);
+
+        (0..8).for_each(|item| {
+            let index = heap.get_index(&item).unwrap();
+            assert_eq!(index.len(), 1);
+            assert_eq!(heap.element_at(index[0]), Some(&item));
+        });
+        // Only the heap property is left to restore.
+        assert!(heap.ensure_valid());
+        assert_eq!(heap.into_sorted_vec(), (0..8).collect::<Vec<i32>>());
+    }
 }
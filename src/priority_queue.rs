@@ -0,0 +1,155 @@
+use crate::{BinaryHeap, HeapKind};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+// A classic priority queue where the priority is kept apart from the payload. Entries are ordered
+// by priority only, and located by item, so the item type doesn't need to be comparable.
+pub struct PriorityQueue<P, I, S = RandomState> {
+    heap: BinaryHeap<Prioritized<P, I>, S>,
+}
+
+impl<P, I, S> PriorityQueue<P, I, S>
+where
+    P: Ord + Clone + std::fmt::Debug,
+    I: Hash + Eq + Clone + std::fmt::Debug,
+    S: BuildHasher + Default,
+{
+    pub fn new(kind: HeapKind) -> Self {
+        Self {
+            heap: BinaryHeap::new(kind),
+        }
+    }
+}
+
+impl<P, I, S> PriorityQueue<P, I, S>
+where
+    P: Ord + Clone + std::fmt::Debug,
+    I: Hash + Eq + Clone + std::fmt::Debug,
+    S: BuildHasher,
+{
+    pub fn push(&mut self, priority: P, item: I) {
+        self.heap.insert_uncapped(Prioritized { priority, item });
+    }
+
+    pub fn pop(&mut self) -> Option<(P, I)> {
+        self.heap
+            .extract_object()
+            .map(|entry| (entry.priority, entry.item))
+    }
+
+    pub fn peek(&self) -> Option<(&P, &I)> {
+        self.heap.peek().map(|entry| (&entry.priority, &entry.item))
+    }
+
+    // Moves `item` to `priority`. If the item was pushed more than once only one of its entries
+    // is changed. Returns false if the item isn't queued.
+    pub fn change_priority(&mut self, item: &I, priority: P) -> bool {
+        // Entries compare equal by item alone, so the probe's priority doesn't matter.
+        let probe = Prioritized {
+            priority: priority.clone(),
+            item: item.clone(),
+        };
+        self.heap.update(&probe, |entry| entry.priority = priority)
+    }
+
+    // Looks `item` up once, handing back either its queued entry or a slot to insert it into.
+    pub fn entry(&mut self, item: I) -> Entry<'_, P, I, S> {
+        match self.heap.find_index_by(&item, |entry| entry.item == item) {
+            Some(index) => Entry::Occupied(OccupiedEntry { queue: self, index }),
+            None => Entry::Vacant(VacantEntry { queue: self, item }),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
+pub enum Entry<'a, P, I, S = RandomState> {
+    Occupied(OccupiedEntry<'a, P, I, S>),
+    Vacant(VacantEntry<'a, P, I, S>),
+}
+
+// An item that is already queued.
+pub struct OccupiedEntry<'a, P, I, S = RandomState> {
+    queue: &'a mut PriorityQueue<P, I, S>,
+    index: usize,
+}
+
+impl<P, I, S> OccupiedEntry<'_, P, I, S>
+where
+    P: Ord + Clone + std::fmt::Debug,
+    I: Hash + Eq + Clone + std::fmt::Debug,
+    S: BuildHasher,
+{
+    fn entry(&self) -> &Prioritized<P, I> {
+        self.queue.heap.element_at(self.index).unwrap()
+    }
+
+    pub fn item(&self) -> &I {
+        &self.entry().item
+    }
+
+    pub fn priority(&self) -> &P {
+        &self.entry().priority
+    }
+
+    pub fn update_priority(self, priority: P) {
+        let item = self.entry().item.clone();
+        self.queue.change_priority(&item, priority);
+    }
+}
+
+// An item that isn't queued yet.
+pub struct VacantEntry<'a, P, I, S = RandomState> {
+    queue: &'a mut PriorityQueue<P, I, S>,
+    item: I,
+}
+
+impl<P, I, S> VacantEntry<'_, P, I, S>
+where
+    P: Ord + Clone + std::fmt::Debug,
+    I: Hash + Eq + Clone + std::fmt::Debug,
+    S: BuildHasher,
+{
+    pub fn item(&self) -> &I {
+        &self.item
+    }
+
+    pub fn insert(self, priority: P) {
+        self.queue.push(priority, self.item);
+    }
+}
+
+// Ordered by priority, hashed and compared for equality by item, which is what the heap's index
+// table needs to find an entry from its item. Hashing writes nothing but the item, so an entry
+// hashes exactly like its item does (see PriorityQueue::entry).
+#[derive(Debug, Clone)]
+struct Prioritized<P, I> {
+    priority: P,
+    item: I,
+}
+
+impl<P: Ord, I: Eq> PartialOrd for Prioritized<P, I> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.priority.cmp(&other.priority))
+    }
+}
+
+impl<P, I: Eq> PartialEq for Prioritized<P, I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.item == other.item
+    }
+}
+
+impl<P, I: Eq> Eq for Prioritized<P, I> {}
+
+impl<P, I: Hash> Hash for Prioritized<P, I> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.item.hash(state);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{BinaryHeap, CapacityExceeded, HeapKind};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::sync::{Mutex, MutexGuard, PoisonError, TryLockError};
+
+// A BinaryHeap behind a mutex, so it can be shared between threads (e.g. in an Arc) as a simple
+// priority channel. Every call takes the lock for just that operation.
+pub struct SyncHeap<T, S = RandomState> {
+    heap: Mutex<BinaryHeap<T, S>>,
+}
+
+impl<T, S> SyncHeap<T, S>
+where
+    T: PartialOrd + Clone + Hash + Eq + std::fmt::Debug,
+    S: BuildHasher + Default,
+{
+    pub fn new(kind: HeapKind) -> Self {
+        Self::from_heap(BinaryHeap::new(kind))
+    }
+}
+
+impl<T, S> SyncHeap<T, S>
+where
+    T: PartialOrd + Clone + Hash + Eq + std::fmt::Debug,
+    S: BuildHasher,
+{
+    pub fn from_heap(heap: BinaryHeap<T, S>) -> Self {
+        Self {
+            heap: Mutex::new(heap),
+        }
+    }
+
+    // Fails like BinaryHeap::insert, if the wrapped heap has a hard capacity and is full.
+    pub fn push(&self, object: T) -> Result<(), CapacityExceeded<T>> {
+        self.lock().insert(object)
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        self.lock().extract_object()
+    }
+
+    // Doesn't wait for the lock: None if another thread holds it or the heap is empty.
+    pub fn try_pop(&self) -> Option<T> {
+        match self.heap.try_lock() {
+            Ok(mut heap) => heap.extract_object(),
+            Err(TryLockError::WouldBlock) => None,
+            Err(TryLockError::Poisoned(poisoned)) => self.recover(poisoned).extract_object(),
+        }
+    }
+
+    // The root can't be borrowed past the lock, so it is cloned.
+    pub fn peek_cloned(&self) -> Option<T> {
+        self.lock().peek_clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub fn into_inner(self) -> BinaryHeap<T, S> {
+        self.heap.into_inner().unwrap_or_else(|poisoned| {
+            let mut heap = poisoned.into_inner();
+            heap.ensure_valid();
+            heap
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BinaryHeap<T, S>> {
+        self.heap
+            .lock()
+            .unwrap_or_else(|poisoned| self.recover(poisoned))
+    }
+
+    // A panic while the lock was held (in PartialOrd, a coalesce merge or the root change
+    // callback) can leave the heap half updated, so a poisoned heap is repaired before it is
+    // handed out again. Clearing the poison makes that happen only once.
+    fn recover<'a>(
+        &self,
+        poisoned: PoisonError<MutexGuard<'a, BinaryHeap<T, S>>>,
+    ) -> MutexGuard<'a, BinaryHeap<T, S>> {
+        let mut heap = poisoned.into_inner();
+        heap.ensure_valid();
+        self.heap.clear_poison();
+        heap
+    }
+}